@@ -0,0 +1,218 @@
+//  Generates the blog post table from `src/blog/posts/*.mdx`, see
+//  src/blog/frontmatter.rs for the file format. Problems with a post are
+//  reported as `compile_error!`s in the generated file so they show up like
+//  any other compile error.
+//
+//  Drafts and scheduled posts are left out of the table, and so out of the
+//  wasm, unless built with `--//:show_drafts`. They are only compiled into the
+//  server, as sources it serves like runtime content.
+
+#[path = "src/blog/extract.rs"]
+mod extract;
+#[path = "src/blog/frontmatter.rs"]
+mod frontmatter;
+#[path = "src/blog/slug.rs"]
+mod slug;
+
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use frontmatter::Post;
+
+const POSTS_DIR: &str = "src/blog/posts";
+
+//  Posts are split into several render functions at `#`/`##` headings once a
+//  part grows past this many bytes, large `mdx!` bodies overflow the number
+//  of locals a wasm function may have.
+const PART_LEN: usize = 12_000;
+
+fn main() {
+    println!("cargo:rerun-if-changed={POSTS_DIR}");
+    println!("cargo:rerun-if-env-changed=SHOW_UNPUBLISHED");
+    let show_unpublished = std::env::var_os("SHOW_UNPUBLISHED").is_some();
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+
+    let mut paths: Vec<_> = fs::read_dir(Path::new(&manifest_dir).join(POSTS_DIR))
+        .unwrap_or_else(|e| panic!("reading {POSTS_DIR}: {e}"))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("mdx".as_ref()))
+        .collect();
+    paths.sort();
+
+    let files: Vec<_> = paths
+        .iter()
+        .map(|path| {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let name = path.file_name().unwrap().to_string_lossy();
+            (
+                format!("{POSTS_DIR}/{name}"),
+                fs::read_to_string(path).unwrap(),
+            )
+        })
+        .collect();
+    let generated = match frontmatter::parse_all(&files) {
+        Ok(posts) => {
+            let bundled =
+                |post: &Post| show_unpublished || post.published && post.publish_at.is_none();
+            let server_only: Vec<_> = files
+                .iter()
+                .filter(|(name, source)| !bundled(&frontmatter::parse(name, source).unwrap()))
+                .collect();
+            let posts: Vec<_> = posts.into_iter().filter(|post| bundled(post)).collect();
+            generate(&posts) + &generate_server_only(&server_only)
+        }
+        Err(errors) => generate_errors(&errors),
+    };
+    fs::write(Path::new(&out_dir).join("posts.rs"), generated).unwrap();
+}
+
+//  `body` split before top-level headings, outside of code fences
+fn parts(body: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut offset = 0;
+    let mut in_fence = false;
+    for line in body.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        let is_heading = line.starts_with("# ") || line.starts_with("## ");
+        if !in_fence && is_heading && offset - start >= PART_LEN {
+            parts.push(&body[start..offset]);
+            start = offset;
+        }
+        offset += line.len();
+    }
+    parts.push(&body[start..]);
+    parts
+}
+
+//  `s` as a raw string literal with enough `#`s to hold any quotes in it
+fn raw_string(s: &str) -> String {
+    let mut hashes = 1;
+    while s.contains(&format!("\"{}", "#".repeat(hashes))) {
+        hashes += 1;
+    }
+    let hashes = "#".repeat(hashes);
+    format!("r{hashes}\"{s}\"{hashes}")
+}
+
+fn generate(posts: &[Post]) -> String {
+    let mut out = String::from("// @generated by build.rs from src/blog/posts\n\n");
+    out += "mod posts {\n    use super::*;\n\n    super::blog_style!();\n";
+    for post in posts {
+        let name = post.slug.replace('-', "_");
+        let parts = parts(&post.body);
+        let _ = writeln!(out, "\n    pub fn post_{name}(_: &Metadata) -> Html {{");
+        out += "        html! {\n            <>\n";
+        for i in 0..parts.len() {
+            let _ = writeln!(out, "            {{post_{name}_{i}()}}");
+        }
+        out += "            </>\n        }\n    }\n";
+        for (i, part) in parts.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "\n    fn post_{name}_{i}() -> Html {{\n        mdx! {{{}}}\n    }}",
+                raw_string(part)
+            );
+        }
+    }
+    out += "}\n\nconst BLOG_POSTS: &[(Metadata, &dyn Fn(&Metadata) -> Html)] = &[\n";
+    for post in posts {
+        let (year, month, day) = post.date;
+        let tags: Vec<_> = post
+            .tags
+            .iter()
+            .map(|t| format!("Cow::Borrowed({t:?})"))
+            .collect();
+        let series = match &post.series {
+            Some((title, part)) => format!(
+                "Some(SeriesPart {{\n                title: Cow::Borrowed({title:?}),\n                part: {part},\n            }})"
+            ),
+            None => "None".into(),
+        };
+        let _ = write!(
+            out,
+            r#"    (
+        Metadata {{
+            date: date!({year} - {month} - {day}),
+            updated: {updated},
+            author: Cow::Borrowed({author:?}),
+            slug: Cow::Borrowed({slug:?}),
+            title: Cow::Borrowed({title:?}),
+            subtitle: Cow::Borrowed({subtitle:?}),
+            published: {published},
+            publish_at: {publish_at},
+            tags: Cow::Borrowed(&[{tags}]),
+            series: {series},
+            toc: {toc},
+            words: {words},
+            headings: Cow::Borrowed(&[{headings}]),
+            fences: Cow::Borrowed(&[{fences}]),
+            text: Cow::Borrowed({text:?}),
+        }},
+        &posts::post_{name},
+    ),
+"#,
+            updated = match post.updated {
+                Some((year, month, day)) => format!("Some(date!({year} - {month} - {day}))"),
+                None => "None".into(),
+            },
+            author = post.author,
+            slug = post.slug,
+            title = post.title,
+            subtitle = post.subtitle,
+            published = post.published,
+            publish_at = post.publish_at.as_ref().map_or("None".into(), |at| {
+                let ((year, month, day), (hour, minute, second)) = (at.date, at.time);
+                let offset = match at.offset_minutes {
+                    0 => "UTC".into(),
+                    m => format!("{}{}:{:02}", if m < 0 { '-' } else { '+' }, m.abs() / 60, m.abs() % 60),
+                };
+                format!("Some(time::macros::datetime!({year}-{month}-{day} {hour}:{minute:02}:{second:02} {offset}))")
+            }),
+            tags = tags.join(", "),
+            toc = post.toc,
+            words = post.words,
+            headings = extract::headings(&post.body)
+                .iter()
+                .map(|(level, text, slug)| format!(
+                    "\n                toc::Heading {{\n                    level: {level},\n                    text: Cow::Borrowed({text:?}),\n                    slug: Cow::Borrowed({slug:?}),\n                }},"
+                ))
+                .collect::<String>(),
+            fences = extract::fences(&post.body)
+                .iter()
+                .map(|info| format!("Cow::Borrowed({info:?})"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            text = extract::plain_text(&post.body),
+            name = post.slug.replace('-', "_"),
+        );
+    }
+    out += "];\n";
+    out
+}
+
+//  the raw files of posts not in `BLOG_POSTS`, empty in the wasm
+fn generate_server_only(files: &[&(String, String)]) -> String {
+    let mut out = String::from(
+        "\n#[cfg(target_arch = \"wasm32\")]\nconst SERVER_POSTS: &[(&str, &str)] = &[];\n",
+    );
+    out += "\n#[cfg(not(target_arch = \"wasm32\"))]\nconst SERVER_POSTS: &[(&str, &str)] = &[\n";
+    for (name, source) in files {
+        let _ = writeln!(out, "    ({name:?}, {}),", raw_string(source));
+    }
+    out += "];\n";
+    out
+}
+
+fn generate_errors(errors: &[String]) -> String {
+    let mut out = String::from("// @generated by build.rs from src/blog/posts\n\n");
+    for e in errors {
+        let _ = writeln!(out, "compile_error!({e:?});");
+    }
+    out += "\nconst BLOG_POSTS: &[(Metadata, &dyn Fn(&Metadata) -> Html)] = &[];\n";
+    out + &generate_server_only(&[])
+}
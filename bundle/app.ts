@@ -1,3 +0,0 @@
-import * as Prism from "prismjs";
-//  reference Prism so it is loaded into bundle
-Prism;
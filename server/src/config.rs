@@ -0,0 +1,347 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use axum::http::HeaderValue;
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use tracing_subscriber::EnvFilter;
+
+use crate::head;
+use crate::logging::LogFormat;
+
+//  Server settings. Each is taken from the first of a command line flag, its
+//  environment variable, the `--config` TOML file and the default, e.g.
+//
+//      listen-addr = "0.0.0.0:8080"
+//      static-dir = "/srv/implfuture/static"
+//      precompressed = true
+//      static-cache-control = "public, max-age=3600"
+//
+//  The asset defaults are set at compile time so the release build can point
+//  them at the optimized bundle.
+#[derive(clap::Args, Deserialize, Default, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Options {
+    /// TOML file with any of the options below, named like the flags
+    #[clap(long, env = "SERVER_CONFIG", global = true)]
+    #[serde(skip)]
+    config: Option<PathBuf>,
+    /// Address to listen on, ignored on lambda [default: 127.0.0.1:8080]
+    #[clap(long, env = "HTTP_LISTEN_ADDR", global = true)]
+    listen_addr: Option<String>,
+    /// Address to serve Prometheus metrics on at `/metrics`, off by default.
+    /// Keep it private, e.g. 127.0.0.1:9090, it is not behind any auth
+    #[clap(long, env = "METRICS_LISTEN_ADDR", global = true)]
+    metrics_listen_addr: Option<String>,
+    /// Public url of the site, for feeds, the sitemap and canonical links
+    /// [default: https://implfuture.dev]
+    #[clap(long, env = "SITE_URL", global = true)]
+    site_url: Option<String>,
+    /// Comma separated paths robots.txt disallows, e.g. `/` on staging
+    #[clap(long, env = "ROBOTS_DISALLOW", global = true, value_delimiter = ',')]
+    robots_disallow: Option<Vec<String>>,
+    /// Directory of posts loaded at runtime and reloaded when they change
+    #[clap(long, env = "CONTENT_DIR", global = true)]
+    content_dir: Option<PathBuf>,
+    /// Key preview links are signed with, at least 16 bytes. Without it
+    /// every preview link is rejected
+    #[clap(long, env = "PREVIEW_SECRET", global = true, hide_env_values = true)]
+    preview_secret: Option<String>,
+    /// Directory served for any path that isn't a page [default: static]
+    #[clap(long, env = "STATIC_DIR", global = true)]
+    static_dir: Option<PathBuf>,
+    /// HTML template pages are rendered into [default: static/index.bzl.html]
+    #[clap(long, env = "INDEX_TEMPLATE", global = true)]
+    index_template: Option<PathBuf>,
+    /// Directory the app's js and wasm are served from [default: .]
+    #[clap(long, env = "ASSETS_DIR", global = true)]
+    assets_dir: Option<PathBuf>,
+    /// Url path of the app's js, relative to the assets directory
+    #[clap(long, env = "APP_JS_PATH", global = true)]
+    app_js_path: Option<String>,
+    /// Url path of the app's wasm, relative to the assets directory
+    #[clap(long, env = "APP_WASM_PATH", global = true)]
+    app_wasm_path: Option<String>,
+    /// Serve the `.br` js and wasm next to the originals to browsers that
+    /// accept brotli
+    #[clap(
+        long,
+        env = "PRECOMPRESSED_ASSETS",
+        global = true,
+        value_parser = clap::builder::BoolishValueParser::new()
+    )]
+    precompressed: Option<bool>,
+    /// Cache-Control of rendered pages [default: no-cache]
+    #[clap(long, env = "PAGE_CACHE_CONTROL", global = true)]
+    page_cache_control: Option<String>,
+    /// Cache-Control of static files and the app's js and wasm, none by default
+    #[clap(long, env = "STATIC_CACHE_CONTROL", global = true)]
+    static_cache_control: Option<String>,
+    /// Seconds to let in-flight requests finish after SIGTERM or ctrl-c
+    /// before exiting with an error [default: 30]
+    #[clap(long, env = "SHUTDOWN_TIMEOUT", global = true)]
+    shutdown_timeout: Option<u64>,
+    /// Which logs to show, like `info` or `warn,server=debug` [default: info]
+    #[clap(long, env = "RUST_LOG", global = true)]
+    log_level: Option<String>,
+    /// Log as text or json lines [default: json on lambda, text otherwise]
+    #[clap(long, env = "LOG_FORMAT", global = true, value_enum)]
+    log_format: Option<LogFormat>,
+}
+
+pub struct Config {
+    pub listen_addr: SocketAddr,
+    pub metrics_listen_addr: Option<SocketAddr>,
+    //  without a trailing `/`
+    pub site_url: String,
+    pub robots_disallow: Vec<String>,
+    //  see `content::load`
+    pub content_dir: Option<PathBuf>,
+    //  see `preview.rs`
+    pub preview_secret: Option<String>,
+    //  the directories and template are only checked for `Mode::Serve`,
+    //  `index_html` is empty otherwise
+    pub static_dir: PathBuf,
+    pub index_template: PathBuf,
+    //  contents of `index_template`
+    pub index_html: String,
+    pub assets_dir: PathBuf,
+    pub app_js_path: String,
+    pub app_wasm_path: String,
+    pub precompressed: bool,
+    pub page_cache_control: HeaderValue,
+    pub static_cache_control: Option<HeaderValue>,
+    pub shutdown_timeout: Duration,
+    pub log_level: String,
+    pub log_format: LogFormat,
+}
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+//  as given on the command line, for `reload_log_level`
+static OPTIONS: OnceCell<Options> = OnceCell::new();
+
+//  set once at startup by `init`
+pub fn get() -> &'static Config {
+    CONFIG
+        .get()
+        .expect("config is loaded before anything is served")
+}
+
+//  what the config is loaded for
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    //  serving or exporting pages, which needs the template and directories
+    Serve,
+    //  commands like `preview` that only read the site settings
+    Site,
+}
+
+pub fn init(options: Options, mode: Mode) -> Result<()> {
+    let _ = OPTIONS.set(options.clone());
+    let config = options.load(mode)?;
+    let _ = CONFIG.set(config);
+    Ok(())
+}
+
+//  The log level as it would be resolved now, re-reading the `--config`
+//  file. A flag or `RUST_LOG` still takes precedence over the file.
+pub fn reload_log_level() -> Result<String> {
+    let options = OPTIONS.get().context("config is not loaded")?;
+    options.log_level(&options.file()?)
+}
+
+impl Options {
+    fn file(&self) -> Result<Options> {
+        Ok(match &self.config {
+            Some(path) => {
+                let toml = fs::read_to_string(path)
+                    .with_context(|| format!("reading config file {}", path.display()))?;
+                toml::from_str(&toml)
+                    .with_context(|| format!("invalid config file {}", path.display()))?
+            }
+            None => Options::default(),
+        })
+    }
+
+    fn log_level(&self, file: &Options) -> Result<String> {
+        let log_level = (self.log_level.as_ref())
+            .or(file.log_level.as_ref())
+            .map_or("info", String::as_str)
+            .to_owned();
+        if let Err(e) = EnvFilter::try_new(&log_level) {
+            bail!("log-level `{log_level}` is not a valid filter: {e}");
+        }
+        Ok(log_level)
+    }
+
+    fn load(self, mode: Mode) -> Result<Config> {
+        let file = self.file()?;
+        let log_level = self.log_level(&file)?;
+
+        let listen_addr = self
+            .listen_addr
+            .or(file.listen_addr)
+            .unwrap_or_else(|| "127.0.0.1:8080".into());
+        let listen_addr = listen_addr.parse().with_context(|| {
+            format!("listen-addr `{listen_addr}` is not an address like 127.0.0.1:8080")
+        })?;
+
+        let metrics_listen_addr = match self.metrics_listen_addr.or(file.metrics_listen_addr) {
+            Some(addr) => Some(addr.parse().with_context(|| {
+                format!("metrics-listen-addr `{addr}` is not an address like 127.0.0.1:9090")
+            })?),
+            None => None,
+        };
+
+        let site_url = self
+            .site_url
+            .or(file.site_url)
+            .unwrap_or_else(|| "https://implfuture.dev".into());
+        if !(site_url.starts_with("https://") || site_url.starts_with("http://")) {
+            bail!("site-url `{site_url}` must start with https:// or http://");
+        }
+        let site_url = site_url.trim_end_matches('/').to_owned();
+        let robots_disallow: Vec<String> = self
+            .robots_disallow
+            .or(file.robots_disallow)
+            .unwrap_or_default()
+            .iter()
+            .map(|path| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(String::from)
+            .collect();
+        if let Some(path) = robots_disallow.iter().find(|path| !path.starts_with('/')) {
+            bail!("robots-disallow `{path}` must start with `/`");
+        }
+        let content_dir = self.content_dir.or(file.content_dir);
+        if let Some(content_dir) = &content_dir {
+            dir(content_dir, "content-dir")?;
+        }
+        let preview_secret = self
+            .preview_secret
+            .or(file.preview_secret)
+            .filter(|secret| !secret.is_empty());
+        if let Some(secret) = &preview_secret {
+            if secret.len() < 16 {
+                bail!("preview-secret must be at least 16 bytes");
+            }
+        }
+
+        let static_dir = self
+            .static_dir
+            .or(file.static_dir)
+            .unwrap_or_else(|| "static".into());
+        let assets_dir = self
+            .assets_dir
+            .or(file.assets_dir)
+            .unwrap_or_else(|| ".".into());
+        let index_template = self
+            .index_template
+            .or(file.index_template)
+            .unwrap_or_else(|| "static/index.bzl.html".into());
+        let index_html = match mode {
+            Mode::Serve => {
+                dir(&static_dir, "static-dir")?;
+                dir(&assets_dir, "assets-dir")?;
+                index_html(&index_template)?
+            }
+            Mode::Site => String::new(),
+        };
+
+        let app_js_path = self
+            .app_js_path
+            .or(file.app_js_path)
+            .unwrap_or_else(|| option_env!("APP_JS_PATH").unwrap_or("/app_wasm.js").into());
+        let app_wasm_path = self
+            .app_wasm_path
+            .or(file.app_wasm_path)
+            .unwrap_or_else(|| {
+                option_env!("APP_WASM_PATH")
+                    .unwrap_or("/app_wasm_bg.wasm")
+                    .into()
+            });
+        for (name, path) in [
+            ("app-js-path", &app_js_path),
+            ("app-wasm-path", &app_wasm_path),
+        ] {
+            if !path.starts_with('/') {
+                bail!("{name} `{path}` must start with `/`");
+            }
+        }
+        let precompressed = self
+            .precompressed
+            .or(file.precompressed)
+            .unwrap_or(option_env!("AXUM_PRECOMPRESSED_WASM").is_some());
+
+        let page_cache_control = self
+            .page_cache_control
+            .or(file.page_cache_control)
+            .unwrap_or_else(|| "no-cache".into());
+        let page_cache_control = header(&page_cache_control, "page-cache-control")?;
+        let static_cache_control = match self.static_cache_control.or(file.static_cache_control) {
+            Some(value) => Some(header(&value, "static-cache-control")?),
+            None => None,
+        };
+        let shutdown_timeout = self
+            .shutdown_timeout
+            .or(file.shutdown_timeout)
+            .unwrap_or(30);
+        let log_format = self.log_format.or(file.log_format).unwrap_or({
+            match lambda_web::is_running_on_lambda() {
+                true => LogFormat::Json,
+                false => LogFormat::Text,
+            }
+        });
+
+        Ok(Config {
+            listen_addr,
+            metrics_listen_addr,
+            site_url,
+            robots_disallow,
+            content_dir,
+            preview_secret,
+            static_dir,
+            index_template,
+            index_html,
+            assets_dir,
+            app_js_path,
+            app_wasm_path,
+            precompressed,
+            page_cache_control,
+            static_cache_control,
+            shutdown_timeout: Duration::from_secs(shutdown_timeout),
+            log_level,
+            log_format,
+        })
+    }
+}
+
+fn index_html(index_template: &Path) -> Result<String> {
+    let index_html = fs::read_to_string(index_template)
+        .with_context(|| format!("index-template {}", index_template.display()))?;
+    //  where `render_page` splices in the head tags, the page and the app
+    for marker in [head::TEMPLATE_TITLE, "<body>", "</head>"] {
+        if !index_html.contains(marker) {
+            bail!(
+                "index-template {} must contain `{marker}`",
+                index_template.display()
+            );
+        }
+    }
+    Ok(index_html)
+}
+
+fn dir(path: &Path, name: &str) -> Result<()> {
+    if !path.is_dir() {
+        bail!("{name} {} is not a directory", path.display());
+    }
+    Ok(())
+}
+
+fn header(value: &str, name: &str) -> Result<HeaderValue> {
+    HeaderValue::from_str(value)
+        .with_context(|| format!("{name} `{value}` is not a valid header value"))
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+
+//  Loads every `.md`/`.mdx` file in `dir` as runtime content, replacing the
+//  compiled post with the same slug. Keeps the current posts on error.
+pub fn load(dir: &Path) -> Result<()> {
+    let mut files = vec![];
+    for entry in fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))? {
+        let path = entry?.path();
+        if !matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("md" | "mdx")
+        ) {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let source =
+            fs::read_to_string(&path).with_context(|| format!("reading {}", path.display()))?;
+        files.push((name, source));
+    }
+    files.sort();
+    let count = files.len();
+    implfuture::blog::content::set(files).map_err(|errors| {
+        anyhow!(
+            "invalid content in {}:\n{}",
+            dir.display(),
+            errors.join("\n")
+        )
+    })?;
+    tracing::info!("loaded {count} posts from {}", dir.display());
+    Ok(())
+}
+
+//  Calls `on_change` with the changed paths whenever something in `dirs`
+//  changes. Watching stops when the returned watcher is dropped.
+pub fn watch(
+    dirs: &[PathBuf],
+    on_change: impl Fn(Vec<PathBuf>) + Send + 'static,
+) -> Result<RecommendedWatcher> {
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .with_context(|| format!("watching {}", dir.display()))?;
+    }
+    std::thread::spawn(move || {
+        while let Ok(event) = rx.recv() {
+            //  editors save in several steps, let them finish
+            std::thread::sleep(Duration::from_millis(100));
+            let mut paths = vec![];
+            for event in std::iter::once(event).chain(rx.try_iter()) {
+                match event {
+                    Ok(event) => paths.extend(event.paths),
+                    Err(e) => tracing::warn!("watching: {e}"),
+                }
+            }
+            if !paths.is_empty() {
+                on_change(paths);
+            }
+        }
+    });
+    Ok(watcher)
+}
@@ -0,0 +1,83 @@
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::{Context, Result};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures::{Stream, StreamExt};
+use notify::RecommendedWatcher;
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_util::sync::CancellationToken;
+
+use crate::content;
+
+pub const RELOAD_PATH: &str = "/_dev/reload";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static RELOAD: Lazy<broadcast::Sender<()>> = Lazy::new(|| broadcast::channel(16).0);
+static SHUTDOWN: Lazy<CancellationToken> = Lazy::new(CancellationToken::new);
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+//  Serves `root`'s posts as runtime content so edits show up without a
+//  rebuild, and tells open tabs to reload whenever a post or anything in
+//  `static/` changes.
+pub fn start(root: &Path) -> Result<RecommendedWatcher> {
+    let posts = root
+        .join("src/blog/posts")
+        .canonicalize()
+        .with_context(|| format!("{} is not the repository root", root.display()))?;
+    let static_dir = root.join("static").canonicalize()?;
+    content::load(&posts)?;
+    ENABLED.store(true, Ordering::Relaxed);
+
+    let watched = [posts.clone(), static_dir];
+    let watcher = content::watch(&watched, move |paths: Vec<PathBuf>| {
+        if paths.iter().any(|p| p.starts_with(&posts)) {
+            //  keep showing the last good version while a post doesn't parse
+            if let Err(e) = content::load(&posts) {
+                tracing::error!("{e:#}");
+                return;
+            }
+        }
+        let _ = RELOAD.send(());
+    })?;
+    tracing::info!("dev mode: watching {}", root.display());
+    Ok(watcher)
+}
+
+//  injected into every page in dev mode
+pub fn reload_script() -> String {
+    format!(
+        r#"
+    <script>
+      const events = new EventSource("{RELOAD_PATH}");
+      events.onmessage = () => location.reload();
+      //  the server restarted, e.g. after a rebuild
+      events.onerror = () => {{ events.onopen = () => location.reload(); }};
+    </script>
+"#
+    )
+}
+
+//  ends every open event stream, otherwise they hold the graceful shutdown
+//  until its timeout. Tabs reconnect once the server is back.
+pub fn shutdown() {
+    SHUTDOWN.cancel();
+}
+
+//  server-sent events, one per change, until `shutdown`
+pub async fn events() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let shutdown = SHUTDOWN.clone();
+    let stream = futures::stream::unfold(RELOAD.subscribe(), |mut rx| async move {
+        match rx.recv().await {
+            Ok(()) | Err(RecvError::Lagged(_)) => Some((Ok(Event::default().data("reload")), rx)),
+            Err(RecvError::Closed) => None,
+        }
+    })
+    .take_until(async move { shutdown.cancelled().await });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
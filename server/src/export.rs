@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use implfuture::{PageContext, Route};
+use yew_router::Routable;
+
+use crate::{config, feed, sitemap};
+
+//  Renders every `Route` exactly like `index` does and writes a tree that any
+//  static file host can serve, e.g. `/blog/foo` -> `blog/foo/index.html`.
+//  Scheduled posts are included if they are live at the time of the export,
+//  drafts never are.
+pub async fn export(out_dir: &Path) -> Result<()> {
+    //  such a build lists drafts on every page, not just their own
+    anyhow::ensure!(
+        !implfuture::blog::SHOW_DRAFTS,
+        "refusing to export drafts, build without --//:show_drafts"
+    );
+    let config = config::get();
+    let now = time::OffsetDateTime::now_utc();
+    let page = PageContext {
+        now,
+        preview: None,
+        query: None,
+    };
+    for route in Route::all(now) {
+        let path = route.to_path();
+        let html = crate::render_page(
+            &config.index_html,
+            path.clone(),
+            HashMap::new(),
+            page.clone(),
+        )
+        .await;
+        write(&page_file(out_dir, &path), html)?;
+    }
+    //  most static hosts serve this for missing files
+    let html = crate::render_page(
+        &config.index_html,
+        Route::NotFound.to_path(),
+        HashMap::new(),
+        page,
+    )
+    .await;
+    write(&out_dir.join("404.html"), html)?;
+
+    let entries = crate::feed_entries(now).await;
+    write(
+        &out_dir.join("rss.xml"),
+        feed::rss(&config.site_url, &entries),
+    )?;
+    write(
+        &out_dir.join("atom.xml"),
+        feed::atom(&config.site_url, &entries),
+    )?;
+    write(
+        &out_dir.join("sitemap.xml"),
+        sitemap::sitemap(&config.site_url, now),
+    )?;
+    write(
+        &out_dir.join("robots.txt"),
+        sitemap::robots(&config.site_url, &config.robots_disallow),
+    )?;
+
+    //  the template is only needed by the server, compared canonically as it
+    //  can be given as `static/index.bzl.html`, `./static/...` or a symlink
+    let template = config
+        .index_template
+        .canonicalize()
+        .with_context(|| format!("index-template {}", config.index_template.display()))?;
+    copy_dir(&config.static_dir, out_dir, &template)?;
+    for asset in [&config.app_js_path, &config.app_wasm_path] {
+        let asset = asset.trim_start_matches('/');
+        copy_file(&config.assets_dir.join(asset), &out_dir.join(asset))?;
+    }
+
+    tracing::info!("exported site to {}", out_dir.display());
+    Ok(())
+}
+
+fn page_file(out_dir: &Path, path: &str) -> PathBuf {
+    let mut file = out_dir.to_owned();
+    file.extend(path.split('/').filter(|s| !s.is_empty()));
+    file.join("index.html")
+}
+
+fn write(path: &Path, contents: String) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+    fs::write(path, contents).with_context(|| format!("writing {}", path.display()))
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<()> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+    fs::copy(from, to)
+        .with_context(|| format!("copying {} to {}", from.display(), to.display()))?;
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path, skip: &Path) -> Result<()> {
+    for entry in fs::read_dir(from).with_context(|| format!("reading {}", from.display()))? {
+        let entry = entry?;
+        let (from, to) = (entry.path(), to.join(entry.file_name()));
+        if from.canonicalize().ok().as_deref() == Some(skip) {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            copy_dir(&from, &to, skip)?;
+        } else {
+            copy_file(&from, &to)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::fmt::Write;
+use std::sync::Arc;
+
+use implfuture::blog::Metadata;
+use time::format_description::well_known::{Rfc2822, Rfc3339};
+use time::OffsetDateTime;
+
+const FEED_TITLE: &str = "impl Future { }";
+const FEED_DESCRIPTION: &str = "Kevin King's blog about rust, the web, and everything in between";
+const FEED_AUTHOR: &str = "Kevin King";
+
+pub struct FeedEntry {
+    pub meta: Arc<Metadata>,
+    //  server-rendered post body
+    pub content: String,
+}
+
+impl FeedEntry {
+    fn url(&self, site_url: &str) -> String {
+        format!("{site_url}/blog/{}", self.meta.slug)
+    }
+
+    fn published(&self) -> OffsetDateTime {
+        self.meta.date.midnight().assume_utc()
+    }
+
+    fn updated(&self) -> OffsetDateTime {
+        self.meta
+            .updated
+            .unwrap_or(self.meta.date)
+            .midnight()
+            .assume_utc()
+    }
+}
+
+pub fn rss(site_url: &str, entries: &[FeedEntry]) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom">
+<channel>
+<title>{title}</title>
+<link>{site_url}/blog</link>
+<description>{description}</description>
+<atom:link href="{site_url}/rss.xml" rel="self" type="application/rss+xml" />
+"#,
+        title = escape(FEED_TITLE),
+        description = escape(FEED_DESCRIPTION),
+    );
+    if let Some(latest) = entries.first() {
+        let _ = writeln!(
+            out,
+            "<lastBuildDate>{}</lastBuildDate>",
+            latest.published().format(&Rfc2822).unwrap_or_default()
+        );
+    }
+    for entry in entries {
+        let url = entry.url(site_url);
+        let _ = write!(
+            out,
+            r#"<item>
+<title>{title}</title>
+<link>{url}</link>
+<guid isPermaLink="true">{url}</guid>
+<pubDate>{date}</pubDate>
+<description>{content}</description>
+</item>
+"#,
+            title = escape(&entry.meta.title),
+            date = entry.published().format(&Rfc2822).unwrap_or_default(),
+            content = escape(&entry.content),
+        );
+    }
+    out += "</channel>\n</rss>\n";
+    out
+}
+
+pub fn atom(site_url: &str, entries: &[FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .map(FeedEntry::updated)
+        .max()
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+<title>{title}</title>
+<subtitle>{description}</subtitle>
+<id>{site_url}/</id>
+<link href="{site_url}/blog" />
+<link href="{site_url}/atom.xml" rel="self" />
+<updated>{updated}</updated>
+<author><name>{author}</name></author>
+"#,
+        title = escape(FEED_TITLE),
+        description = escape(FEED_DESCRIPTION),
+        updated = updated.format(&Rfc3339).unwrap_or_default(),
+        author = escape(FEED_AUTHOR),
+    );
+    for entry in entries {
+        let url = entry.url(site_url);
+        let _ = write!(
+            out,
+            r#"<entry>
+<title>{title}</title>
+<id>{url}</id>
+<link href="{url}" />
+<published>{date}</published>
+<updated>{updated}</updated>
+<summary>{summary}</summary>
+<content type="html">{content}</content>
+</entry>
+"#,
+            title = escape(&entry.meta.title),
+            date = entry.published().format(&Rfc3339).unwrap_or_default(),
+            updated = entry.updated().format(&Rfc3339).unwrap_or_default(),
+            summary = escape(&entry.meta.subtitle),
+            content = escape(&entry.content),
+        );
+    }
+    out += "</feed>\n";
+    out
+}
+
+pub fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out += "&amp;",
+            '<' => out += "&lt;",
+            '>' => out += "&gt;",
+            '"' => out += "&quot;",
+            '\'' => out += "&apos;",
+            c => out.push(c),
+        }
+    }
+    out
+}
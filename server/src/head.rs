@@ -0,0 +1,94 @@
+use implfuture::Route;
+
+use crate::feed::escape;
+
+const SITE_NAME: &str = "impl Future { }";
+const SITE_DESCRIPTION: &str =
+    "Kevin King's blog and projects: rust, the web, virtual reality and bevy";
+const TWITTER_HANDLE: &str = "@4kevinking";
+
+//  the tag in `index.bzl.html` that gets replaced with per-route tags
+pub const TEMPLATE_TITLE: &str = "<title>impl Future { }</title>";
+
+struct PageMeta {
+    title: String,
+    description: String,
+    og_type: &'static str,
+    published: Option<time::Date>,
+    indexable: bool,
+}
+
+fn page_meta(route: Option<&Route>, now: time::OffsetDateTime, preview: bool) -> PageMeta {
+    let page = |title: &str| PageMeta {
+        title: format!("{title} | {SITE_NAME}"),
+        description: SITE_DESCRIPTION.into(),
+        og_type: "website",
+        published: None,
+        indexable: true,
+    };
+    let not_found = PageMeta {
+        indexable: false,
+        ..page("Not Found")
+    };
+    match route {
+        Some(route) if route.is_not_found(now) && !preview => not_found,
+        Some(Route::Home) => PageMeta {
+            title: SITE_NAME.into(),
+            ..page("Home")
+        },
+        Some(Route::BlogIndex) => page("Blog"),
+        Some(Route::Projects) => page("Projects"),
+        Some(Route::Search) => page("Search"),
+        Some(Route::BlogTag { tag }) => page(&format!("Posts tagged #{tag}")),
+        Some(Route::BlogPost { slug }) => match implfuture::blog::find_post(slug, now, preview) {
+            Some(post) => PageMeta {
+                title: format!("{} | {SITE_NAME}", post.title),
+                description: post.subtitle.to_string(),
+                og_type: "article",
+                published: Some(post.date),
+                indexable: post.is_published(now),
+            },
+            None => not_found,
+        },
+        Some(Route::NotFound) | None => not_found,
+    }
+}
+
+//  <title>, description, canonical url and link preview tags for `path`,
+//  `preview` when it was requested with a valid preview token
+pub fn head_tags(
+    site_url: &str,
+    path: &str,
+    route: Option<&Route>,
+    now: time::OffsetDateTime,
+    preview: bool,
+) -> String {
+    let meta = page_meta(route, now, preview);
+    let title = escape(&meta.title);
+    let description = escape(&meta.description);
+    let url = escape(&format!("{site_url}{path}"));
+    let mut tags = format!(
+        r#"<title>{title}</title>
+    <meta name="description" content="{description}" />
+    <link rel="canonical" href="{url}" />
+    <meta property="og:site_name" content="{site_name}" />
+    <meta property="og:title" content="{title}" />
+    <meta property="og:description" content="{description}" />
+    <meta property="og:url" content="{url}" />
+    <meta property="og:type" content="{og_type}" />
+    <meta name="twitter:card" content="summary" />
+    <meta name="twitter:site" content="{TWITTER_HANDLE}" />
+    <meta name="twitter:title" content="{title}" />
+    <meta name="twitter:description" content="{description}" />
+"#,
+        site_name = escape(SITE_NAME),
+        og_type = meta.og_type,
+    );
+    if let Some(date) = meta.published {
+        tags += &format!("    <meta property=\"article:published_time\" content=\"{date}\" />\n");
+    }
+    if !meta.indexable {
+        tags += "    <meta name=\"robots\" content=\"noindex\" />\n";
+    }
+    tags
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::Json;
+use implfuture::{PageContext, Route};
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use yew_router::Routable;
+
+//  Probes for load balancers and orchestrators. They are routed through the
+//  fallback router, so they can't shadow a page.
+
+const NO_STORE: [(header::HeaderName, &str); 1] = [(header::CACHE_CONTROL, "no-store")];
+
+//  the process is up and serving requests
+pub async fn healthz() -> impl IntoResponse {
+    (NO_STORE, "ok")
+}
+
+//  a page can be rendered, by trying the home page on `LOCAL_POOL`
+pub async fn readyz() -> impl IntoResponse {
+    let render = crate::render_app(
+        Route::Home.to_path(),
+        HashMap::new(),
+        PageContext::default(),
+    );
+    let (status, body) = match tokio::time::timeout(Duration::from_secs(5), render).await {
+        Ok(Ok(html)) if !html.is_empty() => (StatusCode::OK, "ready"),
+        Ok(Ok(_)) => (StatusCode::SERVICE_UNAVAILABLE, "rendered an empty page"),
+        Ok(Err(_)) => (StatusCode::SERVICE_UNAVAILABLE, "render panicked"),
+        Err(_) => (StatusCode::SERVICE_UNAVAILABLE, "render timed out"),
+    };
+    (status, NO_STORE, body)
+}
+
+#[derive(Serialize)]
+struct Version {
+    commit: &'static str,
+    built_at: String,
+    posts: usize,
+}
+
+//  Commit and build time are filled in by bazel when stamping, see BUILD.
+//  Unstamped builds keep the `{...}` placeholder.
+fn stamped(value: Option<&'static str>) -> Option<&'static str> {
+    value.filter(|v| !v.starts_with('{'))
+}
+
+pub async fn version() -> impl IntoResponse {
+    let built_at = stamped(option_env!("BUILD_TIMESTAMP"))
+        .and_then(|ts| ts.parse().ok())
+        .and_then(|ts| OffsetDateTime::from_unix_timestamp(ts).ok())
+        .and_then(|ts| ts.format(&Rfc3339).ok());
+    (
+        NO_STORE,
+        Json(Version {
+            commit: stamped(option_env!("BUILD_GIT_COMMIT")).unwrap_or("unknown"),
+            built_at: built_at.unwrap_or_else(|| "unknown".into()),
+            posts: implfuture::blog::visible_posts(OffsetDateTime::now_utc()).count(),
+        }),
+    )
+}
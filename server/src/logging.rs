@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use serde::Deserialize;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+#[derive(clap::ValueEnum, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Text,
+    //  one object per line, with the fields of the current span
+    Json,
+}
+
+//  swaps the filter of the running subscriber, see `set_filter`
+static FILTER: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+//  Logs to stderr, filtered by `filter` in `RUST_LOG` syntax, e.g.
+//  `info,server=debug`. CloudWatch timestamps every line of a Lambda's output
+//  already and doesn't render colors, so both are left out there.
+pub fn init(format: LogFormat, filter: &str) {
+    let lambda = lambda_web::is_running_on_lambda();
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(filter));
+    let fmt = tracing_subscriber::fmt::layer()
+        .with_ansi(!lambda && format == LogFormat::Text)
+        .with_writer(std::io::stderr);
+    let fmt = match (format, lambda) {
+        (LogFormat::Text, false) => fmt.boxed(),
+        (LogFormat::Text, true) => fmt.without_time().boxed(),
+        (LogFormat::Json, false) => fmt.json().with_span_list(false).boxed(),
+        (LogFormat::Json, true) => fmt.json().with_span_list(false).without_time().boxed(),
+    };
+    tracing_subscriber::registry().with(filter).with(fmt).init();
+    let _ = FILTER.set(handle);
+}
+
+//  changes which logs are shown without a restart
+pub fn set_filter(filter: &str) -> Result<()> {
+    let filter = EnvFilter::try_new(filter)
+        .with_context(|| format!("log-level `{filter}` is not a valid filter"))?;
+    FILTER
+        .get()
+        .context("logging is not initialized")?
+        .reload(filter)
+        .context("reloading the log filter")
+}
@@ -1,49 +1,134 @@
+mod config;
+mod content;
+mod dev;
+mod export;
+mod feed;
+mod head;
+mod health;
+mod logging;
+mod metrics;
+mod preview;
+mod sitemap;
+
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::marker::PhantomData;
+use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use axum::body::{Body, BoxBody};
 use axum::extract::Query;
 use axum::http::{header, HeaderMap, HeaderValue, Request, Response, StatusCode};
-use axum::response::{Html, IntoResponse};
+use axum::middleware::{self, Next};
+use axum::response::{Html, IntoResponse, Redirect};
 use axum::routing::{get_service, MethodRouter};
 use axum::Extension;
 use axum::{routing::get, Router};
+use clap::{Parser, Subcommand};
 use futures::future::BoxFuture;
 use futures::ready;
-use implfuture::ServerAppProps;
+use implfuture::blog::PREVIEW_PARAM;
+use implfuture::{PageContext, Route, ServerAppProps, ServerPostProps};
+use metrics::RouteLabel;
 use once_cell::sync::Lazy;
+use tokio::task::JoinError;
 use tokio_util::task::LocalPoolHandle;
 use tower::Service;
 use tower_http::services::ServeDir;
+use tracing::Instrument;
 use yew_router::Routable;
 
-lazy_static::lazy_static!(
-    static ref INDEX_HTML: String = {
-        String::from_utf8( std::fs::read("static/index.bzl.html").unwrap().try_into().unwrap()).unwrap()
-    };
-    static ref APP_WASM_PATH: &'static str = {
-        option_env!("APP_WASM_PATH").unwrap_or("/app_wasm_bg.wasm")
-    };
-    static ref APP_JS_PATH: &'static str = {
-        option_env!("APP_JS_PATH").unwrap_or("/app_wasm.js")
-    };
-);
+const REQUEST_ID: &str = "x-request-id";
 
 static LOCAL_POOL: Lazy<LocalPoolHandle> = Lazy::new(|| LocalPoolHandle::new(num_cpus::get()));
 
-fn html_wasm_init_head() -> String {
-    format!(
+fn html_wasm_init_head(route: Option<&Route>, page: &PageContext) -> String {
+    let config = config::get();
+    let mut head = format!(
         r#"
     <script type="module">
       import init from "{js_path}";
       init("{wasm_path}");
     </script>
 "#,
-        js_path = *APP_JS_PATH,
-        wasm_path = *APP_WASM_PATH,
-    )
+        js_path = config.app_js_path,
+        wasm_path = config.app_wasm_path,
+    );
+    //  what the client needs to hydrate this page
+    head += &format!(
+        "    <script type=\"application/json\" id=\"{}\">{}</script>\n",
+        implfuture::blog::content::CONTENT_ELEMENT_ID,
+        implfuture::blog::content::to_json(route, page),
+    );
+    if dev::enabled() {
+        head += &dev::reload_script();
+    }
+    head
+}
+
+//  SSR `C` on `LOCAL_POOL`, errors if the render panicked. Props aren't
+//  `Send`, so they are made on the pool's thread.
+async fn render<C, P>(props: P) -> Result<String, JoinError>
+where
+    C: yew::BaseComponent,
+    P: FnOnce() -> C::Properties + Send + 'static,
+{
+    //  made here so it belongs to the request's span
+    let span = tracing::info_span!("ssr", component = std::any::type_name::<C>());
+    let queued = metrics::QueuedRender::new();
+    LOCAL_POOL
+        .spawn_pinned(|| {
+            async move {
+                drop(queued);
+                let timer = metrics::RENDER_SECONDS.start_timer();
+                let mut out = String::new();
+                yew::ServerRenderer::<C>::with_props(props())
+                    .render_to_string(&mut out)
+                    .await;
+                let seconds = timer.stop_and_record();
+                tracing::debug!(ms = seconds * 1000.0, "rendered");
+                out
+            }
+            .instrument(span)
+        })
+        .await
+}
+
+//  SSR `path` through `ServerApp`
+async fn render_app(
+    path: String,
+    queries: HashMap<String, String>,
+    page: PageContext,
+) -> Result<String, JoinError> {
+    render::<implfuture::ServerApp, _>(move || ServerAppProps {
+        path: path.into(),
+        queries,
+        page,
+    })
+    .await
+}
+
+//  SSR `path` and splice it into the index template
+async fn render_page(
+    index_html_s: &str,
+    path: String,
+    queries: HashMap<String, String>,
+    page: PageContext,
+) -> String {
+    let route = Route::recognize(&path);
+    let head_tags = head::head_tags(
+        &config::get().site_url,
+        &path,
+        route.as_ref(),
+        page.now,
+        page.preview.is_some(),
+    );
+    let init_head = html_wasm_init_head(route.as_ref(), &page);
+    let out = render_app(path, queries, page).await.unwrap();
+    index_html_s
+        .replace(head::TEMPLATE_TITLE, &head_tags)
+        .replace("<body>", &format!("<body>{}", out))
+        .replace("</head>", &format!("{}</head>", init_head))
 }
 
 async fn index(
@@ -51,66 +136,353 @@ async fn index(
     url: Request<Body>,
     Query(queries): Query<HashMap<String, String>>,
 ) -> impl IntoResponse {
-    let out = LOCAL_POOL
-        .spawn_pinned(|| async move {
-            let props = ServerAppProps {
-                path: url.uri().path().to_owned().into(),
-                queries,
-            };
-            let mut out = String::new();
-            yew::ServerRenderer::<implfuture::ServerApp>::with_props(props)
-                .render_to_string(&mut out)
-                .await;
-            out
-        })
-        .await
-        .unwrap();
-    let html = index_html_s
-        .replace("<body>", &format!("<body>{}", out))
-        .replace("</head>", &format!("{}</head>", html_wasm_init_head()));
+    let path = url.uri().path().to_owned();
+    let route = Route::recognize(&path);
+    let now = time::OffsetDateTime::now_utc();
+    let preview = match (
+        queries.get(PREVIEW_PARAM),
+        &route,
+        &config::get().preview_secret,
+    ) {
+        (None, ..) => None,
+        (Some(token), Some(Route::BlogPost { slug }), Some(secret))
+            if preview::verify(secret, slug, token) =>
+        {
+            Some(slug.clone())
+        }
+        //  drop invalid and expired tokens rather than render a page that
+        //  looks like a preview
+        (Some(_), ..) => return Redirect::temporary(&path).into_response(),
+    };
+    let not_found = match &route {
+        Some(Route::BlogPost { slug }) if preview.is_some() => {
+            implfuture::blog::find_post(slug, now, true).is_none()
+        }
+        Some(route) => route.is_not_found(now),
+        None => false,
+    };
+    let status = match not_found {
+        true => StatusCode::NOT_FOUND,
+        false => StatusCode::OK,
+    };
+    let html = render_page(
+        &index_html_s,
+        path,
+        queries,
+        PageContext {
+            now,
+            preview,
+            query: None,
+        },
+    )
+    .await;
     (
-        HeaderMap::from_iter([(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"))]),
+        status,
+        HeaderMap::from_iter([(
+            header::CACHE_CONTROL,
+            config::get().page_cache_control.clone(),
+        )]),
         Html(html),
     )
+        .into_response()
+}
+
+//  swap the empty 404 from static file serving for the rendered not-found page
+async fn not_found_page(req: Request<Body>, next: Next<Body>) -> Response<BoxBody> {
+    let res = next.run(req).await;
+    if res.status() != StatusCode::NOT_FOUND {
+        return res;
+    }
+    let html = render_page(
+        &config::get().index_html,
+        Route::NotFound.to_path(),
+        HashMap::new(),
+        PageContext::default(),
+    )
+    .await;
+    (StatusCode::NOT_FOUND, Html(html)).into_response()
+}
+
+//  `static_cache_control` for static files and the app's js and wasm
+async fn static_cache_control(req: Request<Body>, next: Next<Body>) -> Response<BoxBody> {
+    let mut res = next.run(req).await;
+    if let Some(value) = &config::get().static_cache_control {
+        if res.status().is_success() {
+            res.headers_mut()
+                .entry(header::CACHE_CONTROL)
+                .or_insert_with(|| value.clone());
+        }
+    }
+    res
+}
+
+async fn render_post(slug: String, now: time::OffsetDateTime) -> String {
+    render::<implfuture::ServerPost, _>(move || ServerPostProps {
+        slug: slug.into(),
+        now,
+    })
+    .await
+    .unwrap()
+}
+
+//  the posts published at `now`
+async fn feed_entries(now: time::OffsetDateTime) -> Vec<feed::FeedEntry> {
+    let posts: Vec<_> = implfuture::blog::visible_posts(now).collect();
+    let mut entries = vec![];
+    for meta in posts {
+        entries.push(feed::FeedEntry {
+            content: render_post(meta.slug.to_string(), now).await,
+            meta,
+        });
+    }
+    entries
+}
+
+async fn rss() -> impl IntoResponse {
+    let entries = feed_entries(time::OffsetDateTime::now_utc()).await;
+    let body = feed::rss(&config::get().site_url, &entries);
+    (
+        [(header::CONTENT_TYPE, "application/rss+xml; charset=utf-8")],
+        body,
+    )
+}
+
+async fn atom() -> impl IntoResponse {
+    let entries = feed_entries(time::OffsetDateTime::now_utc()).await;
+    let body = feed::atom(&config::get().site_url, &entries);
+    (
+        [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+        body,
+    )
+}
+
+async fn sitemap() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        sitemap::sitemap(&config::get().site_url, time::OffsetDateTime::now_utc()),
+    )
+}
+
+async fn robots() -> impl IntoResponse {
+    let config = config::get();
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        sitemap::robots(&config.site_url, &config.robots_disallow),
+    )
 }
 
 async fn handle_error(e: impl std::fmt::Debug) -> impl IntoResponse {
-    eprintln!("{e:?}");
+    tracing::error!(error = ?e, "serving file");
     StatusCode::BAD_REQUEST
 }
 
+#[derive(Parser)]
+#[clap(about = "implfuture.dev server")]
+struct Args {
+    #[clap(flatten)]
+    options: config::Options,
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Render every page to a directory of static html files
+    Export {
+        #[clap(long, default_value = "export")]
+        out_dir: std::path::PathBuf,
+    },
+    /// Serve posts straight from the source tree and reload open pages when
+    /// a post or static file changes
+    Dev {
+        /// Root of the repository
+        #[clap(long, default_value = ".")]
+        root: std::path::PathBuf,
+    },
+    /// Print a link that shows a draft or scheduled post to anyone who has
+    /// it, signed with PREVIEW_SECRET
+    Preview {
+        slug: String,
+        /// Days until the link stops working
+        #[clap(long, default_value = "7")]
+        days: i64,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let mut app_wasm_serve = ServeDir::new(".");
-    if option_env!("AXUM_PRECOMPRESSED_WASM").is_some() {
+    let args = Args::parse();
+    let mode = match args.command {
+        Some(Command::Preview { .. }) => config::Mode::Site,
+        _ => config::Mode::Serve,
+    };
+    config::init(args.options, mode)?;
+    let config = config::get();
+    logging::init(config.log_format, &config.log_level);
+    if let Some(dir) = &config.content_dir {
+        content::load(dir)?;
+    }
+    if let Some(Command::Preview { slug, days }) = &args.command {
+        let secret = config
+            .preview_secret
+            .as_deref()
+            .context("PREVIEW_SECRET must be set to sign preview links")?;
+        implfuture::blog::find_post(slug, time::OffsetDateTime::now_utc(), true)
+            .with_context(|| format!("no post with slug `{slug}`"))?;
+        let token = preview::token(secret, slug, time::Duration::days(*days));
+        println!("{}/blog/{slug}?{PREVIEW_PARAM}={token}", config.site_url);
+        return Ok(());
+    }
+    if let Some(Command::Export { out_dir }) = args.command {
+        return export::export(&out_dir).await;
+    }
+    //  watching stops when these are dropped
+    let _content_watcher = match &config.content_dir {
+        Some(dir) => Some(content::watch(std::slice::from_ref(dir), |_| {
+            if let Err(e) = content::load(dir) {
+                tracing::error!("{e:#}");
+            }
+        })?),
+        None => None,
+    };
+    let _dev_watcher = match &args.command {
+        Some(Command::Dev { root }) => Some(dev::start(root)?),
+        _ => None,
+    };
+    #[cfg(unix)]
+    tokio::spawn(reload_log_level_on_sighup());
+
+    let mut app_wasm_serve = ServeDir::new(&config.assets_dir);
+    if config.precompressed {
         app_wasm_serve = app_wasm_serve.precompressed_br();
     }
-    let app_wasm_serve = get_service(app_wasm_serve).handle_error(handle_error);
-    let static_serve = get_service(ServeDir::new("static")).handle_error(handle_error);
+    let app_wasm_serve = get_service(app_wasm_serve)
+        .handle_error(handle_error)
+        .layer(middleware::from_fn(static_cache_control));
+    let static_serve = get_service(ServeDir::new(&config.static_dir))
+        .handle_error(handle_error)
+        .layer(middleware::from_fn(static_cache_control));
+    let mut router = route(&config.app_js_path, app_wasm_serve.clone())
+        .route(&config.app_wasm_path, app_wasm_serve)
+        .route("/rss.xml", get(rss))
+        .route("/atom.xml", get(atom))
+        .route("/sitemap.xml", get(sitemap))
+        .route("/robots.txt", get(robots))
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .route("/version", get(health::version));
+    if dev::enabled() {
+        router = router.route(dev::RELOAD_PATH, get(dev::events));
+    }
     let route_service = RoutableService::<implfuture::Route, _, _>::new(
         get(index),
-        route(*APP_JS_PATH, app_wasm_serve.clone())
-            .route(*APP_WASM_PATH, app_wasm_serve)
-            .fallback(static_serve),
+        router
+            .fallback(static_serve)
+            .layer(middleware::from_fn(not_found_page)),
     );
-    let route_service = get_service(route_service).layer(Extension(INDEX_HTML.to_string()));
+    let route_service = get_service(route_service).layer(Extension(config.index_html.clone()));
 
     if lambda_web::is_running_on_lambda() {
-        eprintln!("starting server on lambda");
+        tracing::info!("starting server on lambda");
         lambda_web::run_hyper_on_lambda(route_service)
             .await
             .map_err(|e| anyhow::anyhow!("{:?}", e))?;
     } else {
-        let addr = std::env::var("HTTP_LISTEN_ADDR").unwrap_or("127.0.0.1:8080".into());
-        eprintln!("starting server on {}", addr);
-        axum::Server::bind(&addr.parse()?)
+        tracing::info!("starting server on {}", config.listen_addr);
+        if let Some(addr) = config.metrics_listen_addr {
+            tracing::info!("serving metrics on {addr}");
+            let metrics_server = axum::Server::try_bind(&addr)?
+                .serve(route("/metrics", get(metrics::metrics)).into_make_service());
+            tokio::spawn(async move {
+                if let Err(e) = metrics_server.await {
+                    tracing::error!("metrics server failed: {e}");
+                }
+            });
+        }
+        let (draining, drain_started) = tokio::sync::oneshot::channel();
+        let server = axum::Server::bind(&config.listen_addr)
             .serve(get_service(route_service).into_make_service())
-            .await?;
+            //  stops accepting connections and waits for in-flight requests,
+            //  including their renders on `LOCAL_POOL`
+            .with_graceful_shutdown(async {
+                let signal = shutdown_signal().await;
+                tracing::info!(
+                    "received {signal}, draining connections for up to {}s",
+                    config.shutdown_timeout.as_secs()
+                );
+                let _ = draining.send(());
+                dev::shutdown();
+            });
+        let drain_timeout = async {
+            if drain_started.await.is_ok() {
+                tokio::time::sleep(config.shutdown_timeout).await;
+            } else {
+                futures::future::pending::<()>().await;
+            }
+        };
+        tokio::select! {
+            res = server => res?,
+            () = drain_timeout => {
+                anyhow::bail!(
+                    "connections still open after {}s, shutting down anyway",
+                    config.shutdown_timeout.as_secs()
+                );
+            }
+        }
+        tracing::info!("all connections drained, shut down");
     }
 
     Ok(())
 }
 
+//  `kill -HUP` applies a changed `log-level` from the config file
+#[cfg(unix)]
+async fn reload_log_level_on_sighup() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sighup = match signal(SignalKind::hangup()) {
+        Ok(sighup) => sighup,
+        Err(e) => {
+            tracing::warn!("can't listen for SIGHUP: {e}");
+            return;
+        }
+    };
+    while sighup.recv().await.is_some() {
+        match config::reload_log_level().and_then(|level| {
+            logging::set_filter(&level)?;
+            Ok(level)
+        }) {
+            Ok(level) => tracing::info!("log level is now `{level}`"),
+            Err(e) => tracing::error!("keeping the log level: {e:#}"),
+        }
+    }
+}
+
+//  SIGTERM from a container orchestrator, or ctrl-c when run by hand
+async fn shutdown_signal() -> &'static str {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!("can't listen for SIGTERM: {e}");
+                futures::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = futures::future::pending::<()>();
+    tokio::select! {
+        () = ctrl_c => "ctrl-c",
+        () = terminate => "SIGTERM",
+    }
+}
+
 #[derive(Clone)]
 struct RoutableService<R, S: Clone, F: Clone> {
     r: PhantomData<R>,
@@ -134,7 +506,7 @@ impl<R, S: Clone, F: Clone> RoutableService<R, S, F> {
 
 impl<R, S, F> Service<Request<Body>> for RoutableService<R, S, F>
 where
-    R: Routable,
+    R: Routable + PartialEq + RouteLabel,
     S: Service<Request<Body>, Error = Infallible> + Clone,
     S::Response: IntoResponse,
     S::Future: Send + 'static,
@@ -169,25 +541,69 @@ where
 
     //  send known paths to Yew to be SSR'd, otherwise fall-back to `f`
     fn call(&mut self, req: Request<Body>) -> Self::Future {
-        //  TODO: think about how this treats not_found_path
-        match <R as Routable>::recognize(req.uri().path()).is_some() {
-            true => {
+        //  `recognize` returns the `#[not_found]` route for any unknown path,
+        //  those still need to go to `f` so static files can be served
+        let route = <R as Routable>::recognize(req.uri().path())
+            .filter(|r| Some(r) != <R as Routable>::not_found_route().as_ref());
+        let started = Instant::now();
+        //  kept from a proxy in front of us so logs can be joined up
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID)
+            .and_then(|id| id.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= 128)
+            .map(String::from)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let user_agent = req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|ua| ua.to_str().ok())
+            .unwrap_or_default()
+            .to_owned();
+        let span = tracing::info_span!(
+            "request",
+            id = %request_id,
+            method = %req.method(),
+            path = %req.uri().path(),
+        );
+        let (label, fut): (_, Self::Future) = match route {
+            Some(route) => {
                 self.s_ready = false;
                 let fut = self.s.call(req);
-                Box::pin(async move {
-                    let res = fut.await?;
-                    Ok(res.into_response())
-                })
+                (
+                    route.label(),
+                    Box::pin(async move { Ok(fut.await?.into_response()) }),
+                )
             }
-            false => {
+            None => {
                 self.f_ready = false;
+                let label = metrics::fallback_label(req.uri().path());
                 let fut = self.f.call(req);
-                Box::pin(async move {
-                    let res = fut.await?;
-                    Ok(res.into_response())
-                })
+                (
+                    label,
+                    Box::pin(async move { Ok(fut.await?.into_response()) }),
+                )
             }
-        }
+        };
+        Box::pin(
+            async move {
+                let mut res = fut.await?;
+                let elapsed = started.elapsed();
+                metrics::record_request(label, &res, elapsed);
+                tracing::info!(
+                    route = label,
+                    status = res.status().as_u16(),
+                    ms = elapsed.as_secs_f64() * 1000.0,
+                    user_agent,
+                    "served"
+                );
+                if let Ok(id) = HeaderValue::from_str(&request_id) {
+                    res.headers_mut().insert(REQUEST_ID, id);
+                }
+                Ok(res)
+            }
+            .instrument(span),
+        )
     }
 }
 
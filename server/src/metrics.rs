@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use axum::body::{BoxBody, HttpBody};
+use axum::http::{header, Response};
+use axum::response::IntoResponse;
+use implfuture::Route;
+
+use crate::{config, dev};
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    Encoder, Histogram, HistogramVec, IntCounterVec, IntGauge, TextEncoder,
+};
+
+//  Prometheus metrics, served at `/metrics` on `metrics-listen-addr` so they
+//  stay off the public site. Requests are labelled with the `Route` variant
+//  that rendered them, or by `fallback_label` for everything answered by the
+//  fallback router.
+
+lazy_static! {
+    static ref REQUESTS: IntCounterVec = register_int_counter_vec!(
+        "http_requests_total",
+        "Requests by route and response status",
+        &["route", "status"]
+    )
+    .unwrap();
+    static ref REQUEST_SECONDS: HistogramVec = register_histogram_vec!(
+        "http_request_duration_seconds",
+        "Time until the response head is ready, by route",
+        &["route"]
+    )
+    .unwrap();
+    static ref RESPONSE_BYTES: IntCounterVec = register_int_counter_vec!(
+        "http_response_bytes_total",
+        "Bytes of response bodies with a known length, by route",
+        &["route"]
+    )
+    .unwrap();
+    pub static ref RENDER_SECONDS: Histogram = register_histogram!(
+        "ssr_render_duration_seconds",
+        "Time spent rendering on the local pool, excluding time queued"
+    )
+    .unwrap();
+    static ref RENDERS_QUEUED: IntGauge = register_int_gauge!(
+        "ssr_renders_queued",
+        "Renders waiting for a local pool thread"
+    )
+    .unwrap();
+}
+
+pub trait RouteLabel {
+    fn label(&self) -> &'static str;
+}
+
+impl RouteLabel for Route {
+    //  one per variant, slugs and tags would make too many series
+    fn label(&self) -> &'static str {
+        match self {
+            Route::Home => "home",
+            Route::BlogIndex => "blog_index",
+            Route::BlogPost { .. } => "blog_post",
+            Route::BlogTag { .. } => "blog_tag",
+            Route::Projects => "projects",
+            Route::Search => "search",
+            Route::NotFound => "not_found",
+        }
+    }
+}
+
+//  a fixed set of labels for the fallback router, anything that isn't one of
+//  its own routes is a static file or a 404
+pub fn fallback_label(path: &str) -> &'static str {
+    let config = config::get();
+    match path {
+        "/rss.xml" => "rss",
+        "/atom.xml" => "atom",
+        "/sitemap.xml" => "sitemap",
+        "/robots.txt" => "robots",
+        "/healthz" => "healthz",
+        "/readyz" => "readyz",
+        "/version" => "version",
+        dev::RELOAD_PATH => "dev_reload",
+        path if path == config.app_js_path || path == config.app_wasm_path => "app",
+        _ => "static",
+    }
+}
+
+//  counts a render as queued until it starts, or until it's dropped without
+//  starting, e.g. when the pool panics
+pub struct QueuedRender(());
+
+impl QueuedRender {
+    pub fn new() -> Self {
+        RENDERS_QUEUED.inc();
+        QueuedRender(())
+    }
+}
+
+impl Drop for QueuedRender {
+    fn drop(&mut self) {
+        RENDERS_QUEUED.dec();
+    }
+}
+
+pub fn record_request(route: &'static str, res: &Response<BoxBody>, elapsed: Duration) {
+    REQUESTS
+        .with_label_values(&[route, res.status().as_str()])
+        .inc();
+    REQUEST_SECONDS
+        .with_label_values(&[route])
+        .observe(elapsed.as_secs_f64());
+    //  streamed bodies, like dev mode's reload events, aren't counted
+    if let Some(len) = res.body().size_hint().exact() {
+        RESPONSE_BYTES.with_label_values(&[route]).inc_by(len);
+    }
+}
+
+pub async fn metrics() -> impl IntoResponse {
+    let encoder = TextEncoder::new();
+    let mut out = vec![];
+    let _ = encoder.encode(&prometheus::gather(), &mut out);
+    (
+        [
+            (header::CONTENT_TYPE, encoder.format_type().to_owned()),
+            (header::CACHE_CONTROL, "no-store".to_owned()),
+        ],
+        out,
+    )
+}
@@ -0,0 +1,109 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use time::{Duration, OffsetDateTime};
+
+//  Preview links let someone read a draft or scheduled post before it is
+//  published: `/blog/<slug>?preview=<expiry>.<signature>`, where the signature
+//  is an HMAC-SHA256 of the slug and expiry (unix seconds) keyed with
+//  `PREVIEW_SECRET`. Changing the secret revokes every link handed out.
+
+fn mac(secret: &str, slug: &str, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("hmac accepts keys of any length");
+    mac.update(format!("{slug}\n{expires}").as_bytes());
+    mac
+}
+
+pub fn token(secret: &str, slug: &str, valid_for: Duration) -> String {
+    let expires = (OffsetDateTime::now_utc() + valid_for).unix_timestamp();
+    let signature = mac(secret, slug, expires).finalize().into_bytes();
+    format!("{expires}.{}", hex::encode(signature))
+}
+
+pub fn verify(secret: &str, slug: &str, token: &str) -> bool {
+    let Some((expires_s, signature)) = token.split_once('.') else {
+        return false;
+    };
+    let (Ok(expires), Ok(signature)) = (expires_s.parse::<i64>(), hex::decode(signature)) else {
+        return false;
+    };
+    //  `parse` also takes `+123` and `0123`, other spellings of the same token
+    if expires.to_string() != expires_s {
+        return false;
+    }
+    //  `verify_slice` compares in constant time
+    expires > OffsetDateTime::now_utc().unix_timestamp()
+        && mac(secret, slug, expires).verify_slice(&signature).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "0123456789abcdef";
+
+    #[test]
+    fn valid() {
+        let token = token(SECRET, "draft", Duration::days(7));
+        assert!(verify(SECRET, "draft", &token));
+    }
+
+    #[test]
+    fn expired() {
+        let token = token(SECRET, "draft", Duration::seconds(-1));
+        assert!(!verify(SECRET, "draft", &token));
+    }
+
+    #[test]
+    fn wrong_slug() {
+        let token = token(SECRET, "draft", Duration::days(7));
+        assert!(!verify(SECRET, "other-draft", &token));
+        assert!(!verify(SECRET, "draft\n", &token));
+    }
+
+    #[test]
+    fn wrong_secret() {
+        let token = token(SECRET, "draft", Duration::days(7));
+        assert!(!verify("fedcba9876543210", "draft", &token));
+    }
+
+    #[test]
+    fn tampered() {
+        let token = token(SECRET, "draft", Duration::days(7));
+        let (expires, signature) = token.split_once('.').unwrap();
+        //  a later expiry with the old signature
+        let later = expires.parse::<i64>().unwrap() + 1;
+        assert!(!verify(SECRET, "draft", &format!("{later}.{signature}")));
+        //  one flipped bit of the signature
+        let mut bytes = hex::decode(signature).unwrap();
+        bytes[0] ^= 1;
+        let flipped = format!("{expires}.{}", hex::encode(bytes));
+        assert!(!verify(SECRET, "draft", &flipped));
+        //  a truncated signature
+        let truncated = &token[..token.len() - 2];
+        assert!(!verify(SECRET, "draft", truncated));
+        //  the same expiry spelled differently
+        assert!(!verify(SECRET, "draft", &format!("+{token}")));
+        assert!(!verify(SECRET, "draft", &format!("0{token}")));
+    }
+
+    #[test]
+    fn malformed() {
+        for token in [
+            "",
+            ".",
+            "abc",
+            "123",
+            "123.",
+            ".abcd",
+            "x.abcd",
+            "-1.abcd",
+            "123.not-hex",
+            "123.abc",
+            "99999999999999999999.abcd",
+            "123.abcd.ef",
+        ] {
+            assert!(!verify(SECRET, "draft", token), "{token}");
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::Write;
+
+use implfuture::{blog, Route};
+use time::{Date, OffsetDateTime};
+use yew_router::Routable;
+
+use crate::feed::escape;
+
+//  `lastmod` for a page, `None` for pages that must not be listed. Only
+//  published posts count, even in builds that show drafts.
+fn lastmod(route: &Route, now: OffsetDateTime) -> Option<Option<Date>> {
+    let published = || blog::visible_posts(now).filter(|md| md.is_published(now));
+    let newest = |tag: Option<&str>| {
+        published()
+            .filter(|md| match tag {
+                Some(tag) => md.tags.iter().any(|t| t == tag),
+                None => true,
+            })
+            .map(|md| md.updated.unwrap_or(md.date))
+            .max()
+    };
+    match route {
+        Route::BlogPost { slug } => {
+            let meta = blog::find_post(slug, now, false).filter(|md| md.is_published(now))?;
+            Some(Some(meta.updated.unwrap_or(meta.date)))
+        }
+        Route::BlogTag { tag } => Some(Some(newest(Some(tag))?)),
+        Route::Home | Route::BlogIndex => Some(newest(None)),
+        Route::NotFound => None,
+        _ => Some(None),
+    }
+}
+
+//  the pages live at `now`
+pub fn sitemap(site_url: &str, now: OffsetDateTime) -> String {
+    let mut out = String::from(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+"#,
+    );
+    for route in Route::all(now) {
+        let Some(lastmod) = lastmod(&route, now) else {
+            continue;
+        };
+        let _ = write!(
+            out,
+            "<url><loc>{}</loc>",
+            escape(&format!("{site_url}{}", route.to_path()))
+        );
+        if let Some(date) = lastmod {
+            let _ = write!(out, "<lastmod>{date}</lastmod>");
+        }
+        out += "</url>\n";
+    }
+    out += "</urlset>\n";
+    out
+}
+
+pub fn robots(site_url: &str, disallow: &[String]) -> String {
+    let mut out = String::from("User-agent: *\n");
+    if disallow.is_empty() {
+        out += "Allow: /\n";
+    }
+    for path in disallow {
+        let _ = writeln!(out, "Disallow: {path}");
+    }
+    let _ = write!(out, "\nSitemap: {site_url}/sitemap.xml\n");
+    out
+}
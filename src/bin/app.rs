@@ -1,4 +1,5 @@
 fn main() {
     println!("hi!");
-    yew::Renderer::<implfuture::App>::new().hydrate();
+    let page = implfuture::blog::content::load_embedded();
+    yew::Renderer::<implfuture::App>::with_props(implfuture::AppProps { page }).hydrate();
 }
@@ -0,0 +1,271 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use super::{extract, frontmatter, toc::Heading, Metadata, SeriesPart};
+use crate::{PageContext, Route};
+
+//  id of the `<script type="application/json">` the server embeds its clock
+//  and runtime posts in, so the client can hydrate the same page
+pub const CONTENT_ELEMENT_ID: &str = "implfuture-content";
+
+//  Posts loaded at runtime from a content directory rather than compiled in,
+//  they replace compiled posts with the same slug. The server reads them from
+//  disk, the client gets what the page needs from the server, see `to_json`.
+//  Drafts and scheduled posts from `src/blog/posts` are handled the same way
+//  so their sources stay out of the wasm, see build.rs.
+//
+//  A reload swaps in a new `Content`, readers holding the old one keep it
+//  until they drop it.
+struct Content {
+    posts: Vec<Arc<Metadata>>,
+    //  MDX bodies by slug. The server has every post's, the client only the
+    //  one of the post its page shows.
+    bodies: HashMap<String, Arc<str>>,
+    //  whether every post's `Metadata::text` is there, the client only gets
+    //  them on `/search`
+    texts: bool,
+    //  bumped on every reload so caches built from the posts can be dropped
+    generation: u64,
+}
+
+//  a runtime post and its MDX body, if known
+type Post = (Arc<Metadata>, Option<Arc<str>>);
+
+lazy_static! {
+    static ref CONTENT: RwLock<Arc<Content>> = RwLock::new(Arc::new(content(with_builtin(vec![]), true, 0)));
+
+    //  `SERVER_POSTS`, already validated by build.rs
+    static ref BUILTIN: Vec<Post> = {
+        let files: Vec<_> = super::SERVER_POSTS
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect();
+        frontmatter::parse_all(&files)
+            .unwrap()
+            .into_iter()
+            .map(post)
+            .collect()
+    };
+}
+
+//  `posts` and the built-in posts they don't replace
+fn with_builtin(mut posts: Vec<Post>) -> Vec<Post> {
+    for builtin in BUILTIN.iter() {
+        if !posts.iter().any(|(md, _)| md.slug == builtin.0.slug) {
+            posts.push(builtin.clone());
+        }
+    }
+    posts
+}
+
+fn content(posts: Vec<Post>, texts: bool, generation: u64) -> Content {
+    let bodies = posts
+        .iter()
+        .filter_map(|(md, body)| Some((md.slug.to_string(), body.clone()?)))
+        .collect();
+    Content {
+        posts: posts.into_iter().map(|(md, _)| md).collect(),
+        bodies,
+        texts,
+        generation,
+    }
+}
+
+//  Replaces the runtime posts with `files`, `(file name, contents)` pairs in
+//  the same format as `src/blog/posts`. On error the current posts are kept.
+pub fn set(files: Vec<(String, String)>) -> Result<(), Vec<String>> {
+    let posts = frontmatter::parse_all(&files)?
+        .into_iter()
+        .map(post)
+        .collect();
+    replace(with_builtin(posts), true);
+    Ok(())
+}
+
+fn replace(posts: Vec<Post>, texts: bool) {
+    let mut current = CONTENT.write().unwrap();
+    *current = Arc::new(content(posts, texts, current.generation + 1));
+}
+
+pub(crate) fn posts() -> Vec<Arc<Metadata>> {
+    CONTENT.read().unwrap().posts.clone()
+}
+
+pub(crate) fn generation() -> u64 {
+    CONTENT.read().unwrap().generation
+}
+
+pub(crate) fn is_runtime(slug: &str) -> bool {
+    CONTENT
+        .read()
+        .unwrap()
+        .posts
+        .iter()
+        .any(|md| md.slug == slug)
+}
+
+//  the body of the runtime post at `slug`, `None` if the client has to load
+//  its page to get it
+pub(crate) fn body(slug: &str) -> Option<Arc<str>> {
+    CONTENT.read().unwrap().bodies.get(slug).cloned()
+}
+
+//  whether runtime posts can be searched without a page load
+pub(crate) fn has_texts() -> bool {
+    CONTENT.read().unwrap().texts
+}
+
+//  What the client needs to hydrate a page: its `PageContext`, and of the
+//  runtime posts the metadata of the visible ones, for lists and links, the
+//  body of the post the page shows, and on `/search` their texts. Drafts are
+//  only included when they are that post and the page is a preview of it.
+#[derive(Serialize, Deserialize)]
+struct Embedded {
+    #[serde(with = "time::serde::timestamp")]
+    now: time::OffsetDateTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preview: Option<String>,
+    posts: Vec<EmbeddedPost>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EmbeddedPost {
+    meta: Metadata,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+//  What `route` needs to hydrate as json, safe to embed in a `<script>`
+pub fn to_json(route: Option<&Route>, page: &PageContext) -> String {
+    let content = CONTENT.read().unwrap().clone();
+    let shown = match route {
+        Some(Route::BlogPost { slug }) => Some(slug.as_str()),
+        _ => None,
+    };
+    let search = route == Some(&Route::Search);
+    let posts = content
+        .posts
+        .iter()
+        .filter_map(|md| {
+            let is_shown = shown == Some(&*md.slug);
+            let is_preview = page.preview.as_deref() == Some(&*md.slug);
+            if !(md.is_visible(page.now) || is_shown && is_preview) {
+                return None;
+            }
+            Some(EmbeddedPost {
+                meta: (**md).clone(),
+                body: is_shown
+                    .then(|| content.bodies.get(&*md.slug))
+                    .flatten()
+                    .map(|body| body.to_string()),
+                text: search.then(|| md.text.to_string()),
+            })
+        })
+        .collect();
+    let json = serde_json::to_string(&Embedded {
+        now: page.now,
+        preview: page.preview.clone(),
+        posts,
+    })
+    .unwrap();
+    //  `</script>` inside a string would end the element early
+    json.replace("</", "<\\/")
+}
+
+fn from_json(json: &str) -> serde_json::Result<PageContext> {
+    let embedded: Embedded = serde_json::from_str(json)?;
+    let texts = embedded.posts.iter().all(|post| post.text.is_some());
+    let posts: Vec<_> = embedded
+        .posts
+        .into_iter()
+        .map(|post| {
+            let meta = Metadata {
+                text: post.text.map(Cow::Owned).unwrap_or_default(),
+                ..post.meta
+            };
+            (Arc::new(meta), post.body.map(Arc::from))
+        })
+        .collect();
+    //  runtime content, if the server has any, replaces compiled posts
+    if !posts.is_empty() {
+        replace(posts, texts);
+    }
+    Ok(PageContext {
+        now: embedded.now,
+        preview: embedded.preview,
+        query: None,
+    })
+}
+
+//  Loads the content embedded by the server and returns the context the page
+//  was rendered with. Called by the client before hydrating.
+pub fn load_embedded() -> PageContext {
+    let json = web_sys::window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(CONTENT_ELEMENT_ID))
+        .and_then(|e| e.text_content());
+    match json.as_deref().map(from_json) {
+        Some(Ok(page)) => page,
+        Some(Err(e)) => {
+            log::error!("loading embedded content: {e}");
+            PageContext::default()
+        }
+        None => PageContext::default(),
+    }
+}
+
+fn post(post: frontmatter::Post) -> Post {
+    let headings = extract::headings(&post.body)
+        .into_iter()
+        .map(|(level, text, slug)| Heading {
+            level,
+            text: text.into(),
+            slug: slug.into(),
+        })
+        .collect();
+    let meta = Metadata {
+        title: post.title.into(),
+        date: calendar_date(post.date),
+        updated: post.updated.map(calendar_date),
+        author: post.author.into(),
+        slug: post.slug.into(),
+        subtitle: post.subtitle.into(),
+        published: post.published,
+        publish_at: post.publish_at.map(|at| {
+            let (hour, minute, second) = at.time;
+            let offset =
+                time::UtcOffset::from_whole_seconds(i32::from(at.offset_minutes) * 60).unwrap();
+            calendar_date(at.date)
+                .with_hms(hour, minute, second)
+                .unwrap()
+                .assume_offset(offset)
+        }),
+        tags: post.tags.into_iter().map(Cow::Owned).collect(),
+        series: post.series.map(|(title, part)| SeriesPart {
+            title: title.into(),
+            part,
+        }),
+        toc: post.toc,
+        words: post.words,
+        headings,
+        fences: extract::fences(&post.body)
+            .into_iter()
+            .map(Cow::Owned)
+            .collect(),
+        text: extract::plain_text(&post.body).into(),
+    };
+    (Arc::new(meta), Some(post.body.into()))
+}
+
+//  already validated by `frontmatter::parse`
+fn calendar_date((year, month, day): (i32, u8, u8)) -> time::Date {
+    time::Month::try_from(month)
+        .and_then(|month| time::Date::from_calendar_date(year, month, day))
+        .unwrap()
+}
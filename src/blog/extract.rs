@@ -0,0 +1,132 @@
+//  Plain text and headings extracted from a post's MDX. Shared with build.rs,
+//  which runs it on the compiled posts, so only std and pulldown-cmark here.
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+
+use super::slug::Slugger;
+
+const OPTIONS: Options = Options::ENABLE_TABLES.union(Options::ENABLE_STRIKETHROUGH);
+
+//  MDX -> the words a reader sees, separated by single spaces. Markdown
+//  syntax and JSX tags are dropped, code is kept since it is often what
+//  people search for.
+pub fn plain_text(source: &str) -> String {
+    let mut text = String::new();
+    for event in Parser::new_ext(source, OPTIONS) {
+        match event {
+            Event::Text(s) | Event::Code(s) => text += &s,
+            Event::SoftBreak | Event::HardBreak => text.push(' '),
+            //  blocks and table cells don't end in whitespace of their own
+            Event::End(
+                Tag::Paragraph
+                | Tag::Heading(..)
+                | Tag::BlockQuote
+                | Tag::CodeBlock(_)
+                | Tag::Item
+                | Tag::TableCell,
+            ) => text.push(' '),
+            _ => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+//  `(level, text, slug)` of every `#` to `###` heading in document order,
+//  the headings that get an anchor. The text includes inline code but not
+//  JSX, the slugs are unique within the post.
+pub fn headings(source: &str) -> Vec<(usize, String, String)> {
+    let mut out = vec![];
+    let mut slugger = Slugger::default();
+    let mut current: Option<(usize, String)> = None;
+    for event in Parser::new_ext(source, OPTIONS) {
+        match event {
+            Event::Start(Tag::Heading(level, ..)) => {
+                let level = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    _ => continue,
+                };
+                current = Some((level, String::new()));
+            }
+            Event::Text(s) | Event::Code(s) => {
+                if let Some((_, text)) = &mut current {
+                    *text += &s;
+                }
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some((level, text)) = current.take() {
+                    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    let slug = slugger.slug(&text);
+                    out.push((level, text, slug));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+//  Info strings of the fenced code blocks in document order, e.g.
+//  `rust title="src/main.rs" {3-5}`. MDX has no indented code blocks, so those
+//  are skipped rather than counted as a block without info.
+pub fn fences(source: &str) -> Vec<String> {
+    Parser::new_ext(source, OPTIONS)
+        .filter_map(|event| match event {
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(info))) => {
+                Some(info.trim().to_owned())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_is_stripped() {
+        assert_eq!(
+            plain_text("# Title\n\nSome *emphasis* and [a link](https://x.y).\n\n- one\n- two\n"),
+            "Title Some emphasis and a link. one two"
+        );
+    }
+
+    #[test]
+    fn code_is_kept() {
+        assert_eq!(
+            plain_text("Call `foo()`:\n\n```rust\nlet x = foo();\n```\n"),
+            "Call foo(): let x = foo();"
+        );
+    }
+
+    #[test]
+    fn heading_slugs() {
+        let source = "# Intro\n\n## The `Future` trait\n\n#### Deep\n\n## Intro\n\n```\n# not a heading\n```\n";
+        assert_eq!(
+            headings(source),
+            [
+                (1, "Intro".into(), "intro".into()),
+                (2, "The Future trait".into(), "the-future-trait".into()),
+                (2, "Intro".into(), "intro-1".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn jsx_is_dropped() {
+        assert_eq!(
+            plain_text(
+                "Hello <em title=\"rust -> wasm\"><RustString /></em> world\n\n<Counter />\n"
+            ),
+            "Hello world"
+        );
+    }
+
+    #[test]
+    fn fence_info_strings() {
+        let source = "```rust title=\"src/main.rs\" {2}\nfn main() {}\n```\n\n    indented\n\n~~~\nplain\n~~~\n";
+        assert_eq!(fences(source), ["rust title=\"src/main.rs\" {2}", ""]);
+    }
+}
@@ -0,0 +1,437 @@
+//  Parses blog posts, a frontmatter block followed by the MDX body:
+//
+//      ---
+//      title: Rewriting the Modern Web in Rust
+//      date: 2022-10-01
+//      subtitle: Rust, spa, ssr, mdx, yew hooks, bazel
+//      published: true
+//      tags: [rust, web]
+//      ---
+//
+//  `slug` defaults to the file name, `author` to `DEFAULT_AUTHOR`. `updated`,
+//  `series`/`part` and `toc` are optional.
+//  `publish_at: 2023-03-01T09:00:00+01:00` schedules a post, it is published
+//  once the clock passes that time regardless of `published`.
+//
+//  Shared by build.rs, which compiles `src/blog/posts` into the binaries, and
+//  the runtime content loader, so this only depends on std.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+pub const DEFAULT_AUTHOR: &str = "Kevin King";
+
+//  `publish_at`, in the timezone given by `offset_minutes`
+pub struct Timestamp {
+    pub date: (i32, u8, u8),
+    pub time: (u8, u8, u8),
+    pub offset_minutes: i16,
+}
+
+pub struct Post {
+    pub title: String,
+    pub date: (i32, u8, u8),
+    pub updated: Option<(i32, u8, u8)>,
+    pub author: String,
+    pub slug: String,
+    pub subtitle: String,
+    pub published: bool,
+    pub publish_at: Option<Timestamp>,
+    pub tags: Vec<String>,
+    pub series: Option<(String, u32)>,
+    pub toc: bool,
+    pub words: u32,
+    pub body: String,
+}
+
+//  Parses every `(file name, contents)` pair, newest post first. All problems
+//  are collected, including slugs used by more than one file.
+pub fn parse_all(files: &[(String, String)]) -> Result<Vec<Post>, Vec<String>> {
+    let mut posts = vec![];
+    let mut errors = vec![];
+    let mut seen = HashMap::new();
+    for (name, source) in files {
+        match parse(name, source) {
+            Ok(post) => {
+                if let Some(other) = seen.insert(post.slug.clone(), name) {
+                    errors.push(format!(
+                        "duplicate slug `{}` in {other} and {name}",
+                        post.slug
+                    ));
+                }
+                posts.push(post);
+            }
+            Err(e) => errors.push(format!("{name}: {e}")),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    posts.sort_by(|a, b| b.date.cmp(&a.date).then(a.slug.cmp(&b.slug)));
+    Ok(posts)
+}
+
+pub fn parse(name: &str, source: &str) -> Result<Post, String> {
+    let rest = source
+        .strip_prefix("---\n")
+        .ok_or("missing `---` frontmatter block")?;
+    //  the closing `---` may be the last line of a post without a body
+    let (frontmatter, body) = match rest.find("\n---\n") {
+        Some(end) => (&rest[..end], &rest[end + "\n---\n".len()..]),
+        None => (
+            rest.strip_suffix("\n---")
+                .ok_or("unterminated frontmatter, expected a closing `---`")?,
+            "",
+        ),
+    };
+
+    let mut fields = BTreeMap::new();
+    for (i, line) in frontmatter.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(':')
+            .ok_or_else(|| format!("frontmatter line {}: expected `key: value`", i + 2))?;
+        if fields.insert(key.trim(), value.trim()).is_some() {
+            return Err(format!("frontmatter key `{}` is set twice", key.trim()));
+        }
+    }
+    let mut take = |key: &str| fields.remove(key).map(unquote);
+    let required = |value: Option<String>, key: &str| {
+        value.ok_or_else(|| format!("missing frontmatter key `{key}`"))
+    };
+
+    let title = required(take("title"), "title")?;
+    let date = required(take("date"), "date")?;
+    let date =
+        parse_date(&date).ok_or_else(|| format!("malformed date `{date}`, expected YYYY-MM-DD"))?;
+    let updated = match take("updated") {
+        Some(updated) => Some(
+            parse_date(&updated)
+                .ok_or_else(|| format!("malformed updated `{updated}`, expected YYYY-MM-DD"))?,
+        ),
+        None => None,
+    };
+    let author = take("author").unwrap_or_else(|| DEFAULT_AUTHOR.into());
+    let slug = match take("slug") {
+        Some(slug) => slug,
+        None => Path::new(name)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned(),
+    };
+    if slug.is_empty() || !slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!(
+            "slug `{slug}` may only contain ascii letters, digits and `-`"
+        ));
+    }
+    let subtitle = take("subtitle").unwrap_or_default();
+    let published = parse_bool(take("published"), "published")?;
+    let publish_at = match take("publish_at") {
+        Some(at) => Some(parse_timestamp(&at).ok_or_else(|| {
+            format!("malformed publish_at `{at}`, expected YYYY-MM-DDTHH:MM[:SS][Z|+HH:MM]")
+        })?),
+        None => None,
+    };
+    let toc = parse_bool(take("toc"), "toc")?;
+    let tags = match take("tags") {
+        Some(tags) => {
+            parse_list(&tags).ok_or_else(|| format!("malformed tags `{tags}`, expected [a, b]"))?
+        }
+        None => vec![],
+    };
+    let series = match (take("series"), take("part")) {
+        (Some(series), Some(part)) => {
+            let part = part
+                .parse()
+                .map_err(|_| format!("malformed part `{part}`, expected a number"))?;
+            Some((series, part))
+        }
+        (None, None) => None,
+        _ => return Err("`series` and `part` must be set together".into()),
+    };
+    if let Some(key) = fields.keys().next() {
+        return Err(format!("unknown frontmatter key `{key}`"));
+    }
+
+    Ok(Post {
+        title,
+        date,
+        updated,
+        author,
+        slug,
+        subtitle,
+        published,
+        publish_at,
+        tags,
+        series,
+        toc,
+        words: word_count(body),
+        body: body.trim_start_matches('\n').to_owned(),
+    })
+}
+
+//  Words of prose in an MDX body. Code blocks and lines of JSX don't count,
+//  neither does punctuation on its own, like a `-` list marker.
+pub fn word_count(body: &str) -> u32 {
+    let mut in_fence = false;
+    let mut words = 0;
+    for line in body.lines() {
+        let line = line.trim_start();
+        if line.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence || line.starts_with('<') {
+            continue;
+        }
+        words += line
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .count() as u32;
+    }
+    words
+}
+
+fn unquote(s: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = s.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)) {
+            return inner.to_owned();
+        }
+    }
+    s.to_owned()
+}
+
+fn parse_bool(value: Option<String>, key: &str) -> Result<bool, String> {
+    match value.as_deref() {
+        None | Some("false") => Ok(false),
+        Some("true") => Ok(true),
+        Some(other) => Err(format!("malformed {key} `{other}`, expected true or false")),
+    }
+}
+
+fn parse_list(s: &str) -> Option<Vec<String>> {
+    let inner = s.strip_prefix('[')?.strip_suffix(']')?;
+    Some(
+        inner
+            .split(',')
+            .map(|item| unquote(item.trim()))
+            .filter(|item| !item.is_empty())
+            .collect(),
+    )
+}
+
+fn parse_date(s: &str) -> Option<(i32, u8, u8)> {
+    let mut parts = s.split('-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if parts.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i32, u8, u8) =
+        (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&day).then_some((year, month, day))
+}
+
+//  `2023-03-01`, `2023-03-01T09:00`, `2023-03-01 09:00:30Z`,
+//  `2023-03-01T09:00+01:00`. Times without an offset are UTC.
+fn parse_timestamp(s: &str) -> Option<Timestamp> {
+    let (date, rest) = match s.find(['T', ' ']) {
+        Some(i) => (&s[..i], &s[i + 1..]),
+        None => (s, "00:00"),
+    };
+    let date = parse_date(date)?;
+    let (time, offset) = match rest.find(['Z', '+', '-']) {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "Z"),
+    };
+
+    let mut parts = time.split(':');
+    let mut part = |max: u8| -> Option<Option<u8>> {
+        let Some(part) = parts.next() else {
+            return Some(None);
+        };
+        let n: u8 = part.parse().ok()?;
+        (part.len() == 2 && n <= max).then_some(Some(n))
+    };
+    let (hour, minute, second) = (part(23)??, part(59)??, part(59)?.unwrap_or(0));
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let offset_minutes = match offset {
+        "Z" => 0,
+        offset => {
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':')?;
+            let (hours, minutes): (i16, i16) = (hours.parse().ok()?, minutes.parse().ok()?);
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 60 + minutes)
+        }
+    };
+    Some(Timestamp {
+        date,
+        time: (hour, minute, second),
+        offset_minutes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post(frontmatter: &str) -> Result<Post, String> {
+        parse("post.mdx", &format!("---\n{frontmatter}\n---\nbody\n"))
+    }
+
+    #[test]
+    fn minimal() {
+        let post = post("title: Hello\ndate: 2022-10-01").unwrap();
+        assert_eq!(post.title, "Hello");
+        assert_eq!(post.date, (2022, 10, 1));
+        assert_eq!(post.slug, "post");
+        assert_eq!(post.author, DEFAULT_AUTHOR);
+        assert!(!post.published);
+        assert_eq!(post.body, "body\n");
+    }
+
+    #[test]
+    fn missing_closing_delimiter() {
+        let err = parse("post.mdx", "---\ntitle: Hello\ndate: 2022-10-01\nbody\n");
+        assert_eq!(
+            err.err().unwrap(),
+            "unterminated frontmatter, expected a closing `---`"
+        );
+        //  a `---` that isn't on its own line doesn't close the block
+        assert!(parse("post.mdx", "---\ntitle: a---\ndate: 2022-10-01\n").is_err());
+        //  but the last line of the file may close it
+        let post = parse("post.mdx", "---\ntitle: Hello\ndate: 2022-10-01\n---").unwrap();
+        assert_eq!(post.body, "");
+    }
+
+    #[test]
+    fn missing_opening_delimiter() {
+        assert_eq!(
+            parse("post.mdx", "title: Hello\n---\n").err().unwrap(),
+            "missing `---` frontmatter block"
+        );
+    }
+
+    #[test]
+    fn unknown_key() {
+        assert_eq!(
+            post("title: Hello\ndate: 2022-10-01\ncategory: rust")
+                .err()
+                .unwrap(),
+            "unknown frontmatter key `category`"
+        );
+    }
+
+    #[test]
+    fn duplicate_key() {
+        assert_eq!(
+            post("title: a\ntitle: b\ndate: 2022-10-01").err().unwrap(),
+            "frontmatter key `title` is set twice"
+        );
+    }
+
+    #[test]
+    fn bad_dates() {
+        for date in [
+            "2022-13-01",
+            "2022-02-30",
+            "2022-1-01",
+            "22-10-01",
+            "tomorrow",
+        ] {
+            assert!(post(&format!("title: a\ndate: {date}")).is_err(), "{date}");
+        }
+        assert_eq!(
+            post("title: a\ndate: 2024-02-29").unwrap().date,
+            (2024, 2, 29)
+        );
+    }
+
+    #[test]
+    fn bad_timestamps() {
+        for at in [
+            "2023-03-01T24:00",
+            "2023-03-01T09:60",
+            "2023-03-01T9:00",
+            "2023-03-01T09",
+            "2023-03-01T09:00:00:00",
+            "2023-03-01T09:00+0100",
+            "2023-03-01T09:00+24:00",
+            "2023-02-30T09:00",
+            "soon",
+        ] {
+            let err = post(&format!("title: a\ndate: 2022-10-01\npublish_at: {at}"));
+            assert_eq!(
+                err.err().unwrap(),
+                format!("malformed publish_at `{at}`, expected YYYY-MM-DDTHH:MM[:SS][Z|+HH:MM]")
+            );
+        }
+    }
+
+    #[test]
+    fn timestamps() {
+        let at = |s: &str| {
+            let t = parse_timestamp(s).unwrap();
+            (t.date, t.time, t.offset_minutes)
+        };
+        assert_eq!(at("2023-03-01"), ((2023, 3, 1), (0, 0, 0), 0));
+        assert_eq!(at("2023-03-01T09:00"), ((2023, 3, 1), (9, 0, 0), 0));
+        assert_eq!(at("2023-03-01 09:00:30Z"), ((2023, 3, 1), (9, 0, 30), 0));
+        assert_eq!(at("2023-03-01T09:00+01:00"), ((2023, 3, 1), (9, 0, 0), 60));
+        assert_eq!(
+            at("2023-03-01T09:00-05:30"),
+            ((2023, 3, 1), (9, 0, 0), -330)
+        );
+    }
+
+    #[test]
+    fn quoted_values_with_colons() {
+        let post = post(
+            "title: \"Rust: the good parts\"\nsubtitle: 'a: b: c'\ndate: 2022-10-01\n\
+             tags: [\"web: ssr\", rust]\nseries: \"Yew: a series\"\npart: 2\n\
+             publish_at: \"2023-03-01T09:00:00+01:00\"",
+        )
+        .unwrap();
+        assert_eq!(post.title, "Rust: the good parts");
+        assert_eq!(post.subtitle, "a: b: c");
+        assert_eq!(post.tags, ["web: ssr", "rust"]);
+        assert_eq!(post.series, Some(("Yew: a series".into(), 2)));
+        assert_eq!(post.publish_at.unwrap().time, (9, 0, 0));
+    }
+
+    #[test]
+    fn duplicate_slugs() {
+        let file = |name: &str| {
+            (
+                name.to_owned(),
+                "---\ntitle: a\ndate: 2022-10-01\nslug: same\n---\n".to_owned(),
+            )
+        };
+        let errors = parse_all(&[file("a.mdx"), file("b.mdx")]).err().unwrap();
+        assert_eq!(errors, ["duplicate slug `same` in a.mdx and b.mdx"]);
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(
+            word_count("one two\n- three\n```rust\nlet x = 1;\n```\n<Jsx />\n"),
+            3
+        );
+    }
+}
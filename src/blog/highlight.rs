@@ -0,0 +1,487 @@
+//  A small syntax highlighter that runs at render time, both under SSR and in
+//  the browser. It emits the same `token <kind>` class names as Prism so the
+//  prism-vs / prism-vs-dark-plus themes keep working.
+
+use yew::{html, virtual_dom::VText, Html};
+
+struct Lang {
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    //  rust block comments nest, `/* /* */ */` is one comment
+    nested_comments: bool,
+    //  longest delimiters first, e.g. `"""` before `"`
+    quotes: &'static [&'static str],
+    keywords: &'static [&'static str],
+    builtins: &'static [&'static str],
+    booleans: &'static [&'static str],
+    //  rust: `println!`, `'a`, `r#"raw"#`
+    rust: bool,
+    //  capitalized identifiers are types
+    class_names: bool,
+    //  `$VAR` and `${VAR}`
+    variables: bool,
+    //  python `@decorator`
+    decorators: bool,
+    //  json: strings followed by `:` are keys
+    properties: bool,
+}
+
+const RUST: Lang = Lang {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    nested_comments: true,
+    quotes: &["\""],
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+        "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "type",
+        "unsafe", "use", "where", "while",
+    ],
+    builtins: &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+        "u16", "u32", "u64", "u128", "usize",
+    ],
+    booleans: &["true", "false"],
+    rust: true,
+    class_names: true,
+    variables: false,
+    decorators: false,
+    properties: false,
+};
+
+const PYTHON: Lang = Lang {
+    line_comments: &["#"],
+    block_comment: None,
+    nested_comments: false,
+    quotes: &["\"\"\"", "'''", "\"", "'"],
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is",
+        "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while", "with",
+        "yield", "None",
+    ],
+    builtins: &[
+        "bool",
+        "dict",
+        "enumerate",
+        "float",
+        "int",
+        "len",
+        "list",
+        "open",
+        "print",
+        "range",
+        "set",
+        "str",
+        "tuple",
+        "zip",
+    ],
+    booleans: &["True", "False"],
+    rust: false,
+    class_names: false,
+    variables: false,
+    decorators: true,
+    properties: false,
+};
+
+const BASH: Lang = Lang {
+    line_comments: &["#"],
+    block_comment: None,
+    nested_comments: false,
+    quotes: &["\"", "'"],
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "then", "until", "while",
+    ],
+    builtins: &[
+        "cd", "echo", "exit", "printf", "pwd", "read", "set", "source", "test", "unset",
+    ],
+    booleans: &["true", "false"],
+    rust: false,
+    class_names: false,
+    variables: true,
+    decorators: false,
+    properties: false,
+};
+
+const JAVASCRIPT: Lang = Lang {
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    nested_comments: false,
+    quotes: &["\"", "'", "`"],
+    keywords: &[
+        "as",
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "export",
+        "extends",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "switch",
+        "this",
+        "throw",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+    builtins: &["any", "boolean", "number", "string", "unknown", "never"],
+    booleans: &["true", "false"],
+    rust: false,
+    class_names: true,
+    variables: false,
+    decorators: false,
+    properties: false,
+};
+
+const JSON: Lang = Lang {
+    //  not valid json, but used for annotating snippets
+    line_comments: &["//"],
+    block_comment: None,
+    nested_comments: false,
+    quotes: &["\""],
+    keywords: &["null"],
+    builtins: &[],
+    booleans: &["true", "false"],
+    rust: false,
+    class_names: false,
+    variables: false,
+    decorators: false,
+    properties: true,
+};
+
+fn lang(name: &str) -> Option<&'static Lang> {
+    Some(match name {
+        "rust" | "rs" => &RUST,
+        "py" | "python" | "starlark" | "bzl" => &PYTHON,
+        "bash" | "sh" | "shell" | "zsh" => &BASH,
+        "js" | "javascript" | "jsx" | "ts" | "typescript" | "tsx" => &JAVASCRIPT,
+        "json" => &JSON,
+        _ => return None,
+    })
+}
+
+//  Splits `code` into `(kind, text)` pairs, `kind` being the Prism token
+//  class(es) or `None` for plain text. Unknown languages are a single token.
+pub fn tokenize<'a>(lang_name: &str, code: &'a str) -> Vec<(Option<&'static str>, &'a str)> {
+    let Some(lang) = lang(lang_name) else {
+        return vec![(None, code)];
+    };
+    let mut tokens: Vec<(Option<&'static str>, std::ops::Range<usize>)> = vec![];
+    let mut i = 0;
+    while i < code.len() {
+        let rest = &code[i..];
+        let c = rest.chars().next().unwrap_or_default();
+        let (kind, len) = if let Some(len) = comment_len(lang, rest) {
+            (Some("comment"), len)
+        } else if let Some((kind, len)) = string_len(lang, rest) {
+            //  json keys are strings directly followed by a `:`
+            let is_key = lang.properties && rest[len..].trim_start().starts_with(':');
+            (Some(if is_key { "property" } else { kind }), len)
+        } else if c.is_ascii_digit() {
+            (Some("number"), number_len(rest))
+        } else if c.is_alphabetic() || c == '_' {
+            ident(lang, rest)
+        } else if lang.variables && c == '$' {
+            (Some("variable"), variable_len(rest))
+        } else if lang.decorators && c == '@' {
+            (
+                Some("decorator annotation punctuation"),
+                1 + ident_len(&rest[1..]),
+            )
+        } else if "+-*/%=&|!<>^~?:".contains(c) {
+            (Some("operator"), c.len_utf8())
+        } else if "{}[]();,.#".contains(c) {
+            (Some("punctuation"), c.len_utf8())
+        } else {
+            (None, c.len_utf8())
+        };
+        match tokens.last_mut() {
+            //  merge runs of plain text so the output stays small
+            Some((None, range)) if kind.is_none() => range.end = i + len,
+            _ => tokens.push((kind, i..i + len)),
+        }
+        i += len;
+    }
+    tokens
+        .into_iter()
+        .map(|(kind, range)| (kind, &code[range]))
+        .collect()
+}
+
+//  highlighted html for each line of `code`, tokens spanning several lines
+//  (block comments, multi-line strings) are split at the line breaks
+pub fn highlight_lines(lang_name: &str, code: &str) -> Vec<Html> {
+    let mut lines = vec![vec![]];
+    for (kind, text) in tokenize(lang_name, code) {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(vec![]);
+            }
+            if part.is_empty() {
+                continue;
+            }
+            let part = match kind {
+                Some(kind) => html! { <span class={format!("token {kind}")}>{part}</span> },
+                None => VText::new(part.to_owned()).into(),
+            };
+            lines.last_mut().unwrap().push(part);
+        }
+    }
+    if code.ends_with('\n') {
+        lines.pop();
+    }
+    lines.into_iter().map(|l| l.into_iter().collect()).collect()
+}
+
+//  digits, suffixes like `u32` and a decimal point, but not a `..` range
+fn number_len(s: &str) -> usize {
+    let mut len = ident_len(s);
+    if s[len..].starts_with('.') && s[len + 1..].starts_with(|c: char| c.is_ascii_digit()) {
+        len += 1 + ident_len(&s[len + 1..]);
+    }
+    len
+}
+
+fn ident_len(s: &str) -> usize {
+    s.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(s.len())
+}
+
+fn comment_len(lang: &Lang, s: &str) -> Option<usize> {
+    if lang.line_comments.iter().any(|p| s.starts_with(p)) {
+        return Some(s.find('\n').unwrap_or(s.len()));
+    }
+    let (open, close) = lang.block_comment?;
+    if !s.starts_with(open) {
+        return None;
+    }
+    if !lang.nested_comments {
+        return Some(
+            s[open.len()..]
+                .find(close)
+                .map_or(s.len(), |end| open.len() + end + close.len()),
+        );
+    }
+    let mut depth = 0;
+    let mut i = 0;
+    while i < s.len() {
+        if s[i..].starts_with(open) {
+            depth += 1;
+            i += open.len();
+        } else if s[i..].starts_with(close) {
+            depth -= 1;
+            i += close.len();
+            if depth == 0 {
+                return Some(i);
+            }
+        } else {
+            i += s[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    Some(s.len())
+}
+
+fn string_len(lang: &Lang, s: &str) -> Option<(&'static str, usize)> {
+    if lang.rust {
+        if let Some(len) = rust_raw_string_len(s) {
+            return Some(("string", len));
+        }
+        if s.starts_with('\'') {
+            return Some(rust_quote(s));
+        }
+    }
+    let quote = lang.quotes.iter().find(|q| s.starts_with(**q))?;
+    let mut escaped = false;
+    for (idx, c) in s[quote.len()..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if s[quote.len() + idx..].starts_with(quote) {
+            return Some(("string", quote.len() + idx + quote.len()));
+        }
+    }
+    Some(("string", s.len()))
+}
+
+//  `r"..."`, `r#"..."#`, `br"..."`
+fn rust_raw_string_len(s: &str) -> Option<usize> {
+    let prefix = s.strip_prefix('b').unwrap_or(s).strip_prefix('r')?;
+    let hashes = prefix.len() - prefix.trim_start_matches('#').len();
+    let body = prefix[hashes..].strip_prefix('"')?;
+    let close = format!("\"{}", "#".repeat(hashes));
+    let start = s.len() - body.len();
+    Some(
+        body.find(&close)
+            .map_or(s.len(), |end| start + end + close.len()),
+    )
+}
+
+//  `'a'` and `'\n'` are chars, `'a` is a lifetime
+fn rust_quote(s: &str) -> (&'static str, usize) {
+    let mut chars = s.char_indices().skip(1);
+    match (chars.next(), chars.next()) {
+        (Some((_, '\\')), _) => {
+            let end = s[2..].find('\'').map_or(s.len(), |e| 2 + e + 1);
+            ("char", end)
+        }
+        (Some(_), Some((idx, '\''))) => ("char", idx + 1),
+        _ => ("lifetime-annotation symbol", 1 + ident_len(&s[1..])),
+    }
+}
+
+fn variable_len(s: &str) -> usize {
+    match s[1..].strip_prefix('{') {
+        Some(rest) => rest.find('}').map_or(s.len(), |end| 2 + end + 1),
+        None => 1 + ident_len(&s[1..]),
+    }
+}
+
+fn ident(lang: &Lang, s: &str) -> (Option<&'static str>, usize) {
+    let len = ident_len(s);
+    let word = &s[..len];
+    let next = s[len..].chars().next();
+    let kind = if lang.rust && next == Some('!') && !s[len..].starts_with("!=") {
+        return (Some("macro property"), len + 1);
+    } else if lang.booleans.contains(&word) {
+        "boolean"
+    } else if lang.keywords.contains(&word) {
+        "keyword"
+    } else if lang.builtins.contains(&word) {
+        "builtin"
+    } else if next == Some('(') {
+        "function"
+    } else if lang.class_names && word.starts_with(|c: char| c.is_uppercase()) {
+        "class-name"
+    } else {
+        return (None, len);
+    };
+    (Some(kind), len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds<'a>(lang: &str, code: &'a str) -> Vec<(Option<&'static str>, &'a str)> {
+        tokenize(lang, code)
+            .into_iter()
+            .filter(|(_, text)| !text.trim().is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn raw_strings() {
+        assert_eq!(
+            kinds("rust", r##"let s = r#"a "quoted" \ b"#;"##),
+            vec![
+                (Some("keyword"), "let"),
+                (None, " s "),
+                (Some("operator"), "="),
+                (Some("string"), r##"r#"a "quoted" \ b"#"##),
+                (Some("punctuation"), ";"),
+            ]
+        );
+        assert_eq!(
+            kinds("rust", r#"br"\d+""#),
+            vec![(Some("string"), r#"br"\d+""#)]
+        );
+        //  unterminated raw strings run to the end
+        assert_eq!(
+            kinds("rust", r###"r##"a"#"###),
+            vec![(Some("string"), r###"r##"a"#"###)]
+        );
+    }
+
+    #[test]
+    fn lifetimes_and_chars() {
+        assert_eq!(
+            kinds("rust", "&'a str"),
+            vec![
+                (Some("operator"), "&"),
+                (Some("lifetime-annotation symbol"), "'a"),
+                (Some("builtin"), "str"),
+            ]
+        );
+        assert_eq!(kinds("rust", "'a'"), vec![(Some("char"), "'a'")]);
+        assert_eq!(kinds("rust", r"'\n'"), vec![(Some("char"), r"'\n'")]);
+        assert_eq!(kinds("rust", "'é'"), vec![(Some("char"), "'é'")]);
+        assert_eq!(
+            kinds("rust", "'static"),
+            vec![(Some("lifetime-annotation symbol"), "'static")]
+        );
+        //  python has no lifetimes, `'a'` is a string
+        assert_eq!(kinds("py", "'a'"), vec![(Some("string"), "'a'")]);
+    }
+
+    #[test]
+    fn comments() {
+        assert_eq!(
+            kinds("rust", "/* a /* b */ c */ x"),
+            vec![(Some("comment"), "/* a /* b */ c */"), (None, " x")]
+        );
+        //  javascript comments don't nest
+        assert_eq!(
+            kinds("js", "/* a /* b */ c"),
+            vec![(Some("comment"), "/* a /* b */"), (None, " c")]
+        );
+        assert_eq!(
+            kinds("rust", "/* one\ntwo */\nx // end"),
+            vec![
+                (Some("comment"), "/* one\ntwo */"),
+                (None, "\nx "),
+                (Some("comment"), "// end"),
+            ]
+        );
+        assert_eq!(kinds("rust", "/* open"), vec![(Some("comment"), "/* open")]);
+    }
+
+    #[test]
+    fn tokens_spanning_lines() {
+        let code = "let s = \"a\nb\";\n/* c\nd */\n";
+        assert!(tokenize("rust", code).contains(&(Some("string"), "\"a\nb\"")));
+        assert_eq!(highlight_lines("rust", code).len(), 4);
+        assert_eq!(
+            kinds("py", "x = \"\"\"a\n\"b\"\n\"\"\""),
+            vec![
+                (None, "x "),
+                (Some("operator"), "="),
+                (Some("string"), "\"\"\"a\n\"b\"\n\"\"\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_language() {
+        assert_eq!(tokenize("brainfuck", "+[-]"), vec![(None, "+[-]")]);
+    }
+}
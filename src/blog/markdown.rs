@@ -0,0 +1,113 @@
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use yew::{html, virtual_dom::VText, Html};
+
+use super::{html_text, HighlightCode, MyBlockquote, MyCode, MyH1, MyH2, MyH3, MyLi, MyP, MyUl};
+
+//  Renders a runtime post's markdown to `Html`, mapping elements to the same
+//  components as `blog_style!` does for compiled posts. JSX is limited to the
+//  self-closing components listed in `super::component`, any other inline
+//  HTML is dropped.
+pub fn render(source: &str) -> Html {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    //  children of each element that is still open, innermost last
+    let mut stack: Vec<Vec<Html>> = vec![vec![]];
+    let mut in_table_head = false;
+    for event in Parser::new_ext(source, options) {
+        let node = match event {
+            Event::Start(tag) => {
+                in_table_head |= matches!(tag, Tag::TableHead);
+                stack.push(vec![]);
+                continue;
+            }
+            Event::End(tag) => {
+                let ends_table_head = matches!(tag, Tag::TableHead);
+                let children = stack.pop().unwrap_or_default();
+                let node = element(tag, children, in_table_head);
+                in_table_head &= !ends_table_head;
+                node
+            }
+            Event::Text(text) => VText::new(text.to_string()).into(),
+            Event::Code(code) => html! { <MyCode>{code.to_string()}</MyCode> },
+            Event::Html(raw) => component(&raw),
+            Event::SoftBreak => VText::new("\n").into(),
+            Event::HardBreak => html! { <br /> },
+            Event::Rule => html! { <hr /> },
+            Event::TaskListMarker(checked) => {
+                html! { <input type="checkbox" disabled=true checked={checked} /> }
+            }
+            Event::FootnoteReference(name) => html! { <sup>{name.to_string()}</sup> },
+        };
+        if let Some(children) = stack.last_mut() {
+            children.push(node);
+        }
+    }
+    stack.into_iter().flatten().collect()
+}
+
+//  `<Counter />` and friends
+fn component(raw: &str) -> Html {
+    let name = raw
+        .trim()
+        .strip_prefix('<')
+        .and_then(|s| s.strip_suffix("/>"))
+        .map(str::trim);
+    name.and_then(super::component).unwrap_or_default()
+}
+
+fn element(tag: Tag, children: Vec<Html>, in_table_head: bool) -> Html {
+    match tag {
+        Tag::Paragraph => html! { <MyP>{for children}</MyP> },
+        Tag::Heading(HeadingLevel::H1, ..) => html! { <MyH1>{for children}</MyH1> },
+        Tag::Heading(HeadingLevel::H2, ..) => html! { <MyH2>{for children}</MyH2> },
+        Tag::Heading(HeadingLevel::H3, ..) => html! { <MyH3>{for children}</MyH3> },
+        Tag::Heading(level, ..) => {
+            html! { <@{level.to_string()}>{for children}</@> }
+        }
+        Tag::BlockQuote => html! { <MyBlockquote>{for children}</MyBlockquote> },
+        Tag::CodeBlock(kind) => {
+            let mut code = String::new();
+            for c in &children {
+                html_text(c, &mut code);
+            }
+            let info = match kind {
+                CodeBlockKind::Fenced(info) => info.trim().to_owned(),
+                CodeBlockKind::Indented => String::new(),
+            };
+            html! {
+                <HighlightCode info={info}>
+                    <code>{code}</code>
+                </HighlightCode>
+            }
+        }
+        Tag::List(None) => html! { <MyUl>{for children}</MyUl> },
+        Tag::List(Some(start)) => html! { <ol start={start.to_string()}>{for children}</ol> },
+        Tag::Item => html! { <MyLi>{for children}</MyLi> },
+        Tag::FootnoteDefinition(name) => {
+            html! { <div id={name.to_string()} class="text-sm">{for children}</div> }
+        }
+        Tag::Table(_) => {
+            //  browsers add the `<tbody>` when parsing anyway, which would
+            //  break hydration if it were left out
+            let mut children = children.into_iter();
+            let head = children.next();
+            html! { <table>{for head}<tbody>{for children}</tbody></table> }
+        }
+        Tag::TableHead => html! { <thead><tr>{for children}</tr></thead> },
+        Tag::TableRow => html! { <tr>{for children}</tr> },
+        Tag::TableCell if in_table_head => html! { <th>{for children}</th> },
+        Tag::TableCell => html! { <td>{for children}</td> },
+        Tag::Emphasis => html! { <em>{for children}</em> },
+        Tag::Strong => html! { <strong>{for children}</strong> },
+        Tag::Strikethrough => html! { <del>{for children}</del> },
+        Tag::Link(_, href, title) => html! {
+            <a href={href.to_string()} title={title.to_string()}>{for children}</a>
+        },
+        Tag::Image(_, src, title) => {
+            let mut alt = String::new();
+            for c in &children {
+                html_text(c, &mut alt);
+            }
+            html! { <img src={src.to_string()} alt={alt} title={title.to_string()} /> }
+        }
+    }
+}
@@ -1,20 +1,38 @@
-mod p04_pytorch;
-mod p05_pytorch_p2;
-mod post1;
-mod post2;
-mod post3;
+pub mod content;
+mod extract;
+mod frontmatter;
+mod highlight;
+mod markdown;
+mod search;
+mod slug;
 mod syntaxhighlight;
+mod toc;
 
-use std::borrow::Borrow;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 
 use time::macros::date;
 use yew::{
-    function_component, html, include_mdx, mdx, mdx_style, use_callback, use_state, Children, Html,
-    Properties,
+    function_component, hook, html, mdx, mdx_style, use_callback, use_context,
+    use_effect_with_deps, use_mut_ref, use_state, virtual_dom::VNode, AttrValue, Children,
+    ContextProvider, Html, Properties,
 };
-use yew_router::prelude::Link;
+use yew_router::prelude::{Link, Routable};
+
+pub use search::Search;
 
-use crate::{blog::syntaxhighlight::HighlightCode, Route};
+use crate::{
+    blog::{
+        syntaxhighlight::HighlightCode,
+        toc::{Heading, TableOfContents},
+    },
+    PageContext, Route,
+};
 
 macro_rules! blog_style {
     () => {
@@ -39,21 +57,85 @@ pub struct ChildProps {
     children: Children,
 }
 
-const HEADER_LINK_LEN: usize = 20;
+//  What was extracted from the body of the post being rendered, handed out
+//  to its components in document order
+#[derive(Clone)]
+struct BodyContext {
+    headings: Cow<'static, [Heading]>,
+    next_heading: Rc<Cell<usize>>,
+    fences: Cow<'static, [Cow<'static, str>]>,
+    next_fence: Rc<Cell<usize>>,
+}
 
-#[function_component]
-fn MyH1(c: &ChildProps) -> Html {
-    let mut tag = String::new();
-    for c in c.children.iter() {
-        match c {
-            yew::virtual_dom::VNode::VText(t) => {
-                tag += &t.text.to_string();
+impl BodyContext {
+    fn new(meta: &Metadata) -> Self {
+        BodyContext {
+            headings: meta.headings.clone(),
+            next_heading: Default::default(),
+            fences: meta.fences.clone(),
+            next_fence: Default::default(),
+        }
+    }
+}
+
+impl PartialEq for BodyContext {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.next_heading, &other.next_heading)
+    }
+}
+
+//  Anchor slug of the next heading, the same the table of contents links to.
+//  Taken once per heading.
+#[hook]
+fn use_heading_slug() -> String {
+    let body = use_context::<BodyContext>();
+    let slug = use_state(move || {
+        let body = body?;
+        let i = body.next_heading.get();
+        body.next_heading.set(i + 1);
+        Some(body.headings.get(i)?.slug.to_string())
+    });
+    (*slug).clone().unwrap_or_default()
+}
+
+//  Info string of the next fenced code block, unless the block was rendered
+//  from markdown and comes with its own `info`. Taken once per block.
+#[hook]
+fn use_fence_info(info: Option<AttrValue>) -> AttrValue {
+    let body = use_context::<BodyContext>();
+    let info = use_state(move || {
+        info.or_else(|| {
+            let body = body?;
+            let i = body.next_fence.get();
+            body.next_fence.set(i + 1);
+            Some(body.fences.get(i)?.to_string().into())
+        })
+    });
+    (*info).clone().unwrap_or_default()
+}
+
+//  Visible text of `html`, descending into elements and fragments. Component
+//  children are opaque, so text inside e.g. a nested component is skipped.
+fn html_text(html: &Html, out: &mut String) {
+    match html {
+        VNode::VText(t) => *out += &t.text,
+        VNode::VTag(t) => {
+            for c in t.children().iter() {
+                html_text(c, out);
+            }
+        }
+        VNode::VList(l) => {
+            for c in l.iter() {
+                html_text(c, out);
             }
-            _ => (),
-        };
+        }
+        _ => (),
     }
-    tag = tag.replace(" ", "-").to_lowercase();
-    tag.truncate(HEADER_LINK_LEN);
+}
+
+#[function_component]
+fn MyH1(c: &ChildProps) -> Html {
+    let tag = use_heading_slug();
     html! {
       <h1 id={tag.clone()} class="text-4xl pt-10 pb-6">
         <a class="text-inherit" href={format!("#{tag}")}>
@@ -65,17 +147,7 @@ fn MyH1(c: &ChildProps) -> Html {
 
 #[function_component]
 fn MyH2(c: &ChildProps) -> Html {
-    let mut tag = String::new();
-    for c in c.children.iter() {
-        match c {
-            yew::virtual_dom::VNode::VText(t) => {
-                tag += &t.text.to_string();
-            }
-            _ => (),
-        };
-    }
-    tag = tag.replace(" ", "-").to_lowercase();
-    tag.truncate(HEADER_LINK_LEN);
+    let tag = use_heading_slug();
     html! {
       <h2 id={tag.clone()} class="text-2xl pt-8 pb-4">
         <a class="text-inherit" href={format!("#{tag}")}>
@@ -87,7 +159,7 @@ fn MyH2(c: &ChildProps) -> Html {
 
 #[function_component]
 fn MyH3(c: &ChildProps) -> Html {
-    let tag = children_to_slug(c.children.iter());
+    let tag = use_heading_slug();
     html! {
       <h3 id={tag.clone()} class="text-xl pt-6 pb-2">
         <a class="text-inherit" href={format!("#{tag}")}>
@@ -97,27 +169,6 @@ fn MyH3(c: &ChildProps) -> Html {
     }
 }
 
-fn children_to_slug(c: impl IntoIterator<Item = Html>) -> String {
-    let mut out = children_to_string(c);
-    out.truncate(HEADER_LINK_LEN);
-    out
-}
-
-fn children_to_string<H: Borrow<Html>>(c: impl IntoIterator<Item = H>) -> String {
-    let mut out = String::new();
-    for c in c.into_iter() {
-        match c.borrow() {
-            yew::virtual_dom::VNode::VText(t) => {
-                out += &t.text.to_string();
-            }
-            _ => (),
-        };
-    }
-    out = out.replace(" ", "-").to_lowercase();
-
-    out
-}
-
 #[function_component]
 fn MyPre(c: &ChildProps) -> Html {
     html! {
@@ -204,97 +255,430 @@ fn RustString() -> Html {
     }
 }
 
+//  components that runtime content can use as `<Name />`
+fn component(name: &str) -> Option<Html> {
+    Some(match name {
+        "Counter" => html! { <Counter /> },
+        "RustString" => html! { <RustString /> },
+        _ => return None,
+    })
+}
+
+//  Borrowed for compiled posts, owned for runtime content
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Metadata {
-    title: &'static str,
-    date: time::Date,
-    slug: &'static str,
-    subtitle: &'static str,
-    published: bool,
-}
-
-const BLOG_POSTS: &[(Metadata, &dyn Fn(&Metadata) -> Html)] = &[
-    (
-        Metadata {
-            date: date!(2023 - 2 - 20),
-            slug: "nanogpt-rust-part-2",
-            title: "nanoGPT + Rust :: Part 2",
-            subtitle: "Mixing Python Notebooks + Rust",
-            published: false,
-        },
-        &p05_pytorch_p2::post_5,
-    ),
-    (
-        Metadata {
-            date: date!(2023 - 2 - 19),
-            slug: "nanogpt-rust-part-1",
-            title: "nanoGPT + Rust :: Part 1",
-            subtitle: "Mixing Python Notebooks + Rust",
-            published: true,
-        },
-        &p04_pytorch::post_4,
-    ),
-    (
-        Metadata {
-            date: date!(2022 - 10 - 7),
-            slug: "using-rust-docs-effectively",
-            title: "Using Rust Docs Effectively",
-            subtitle: "docs.rs, cargo doc tips",
-            published: false,
-        },
-        &post3::post_3,
-    ),
-    (
-        Metadata {
-            date: date!(2022 - 10 - 1),
-            slug: "rewriting-the-modern-web-in-rust",
-            title: "Rewriting the Modern Web in Rust",
-            subtitle: "Rust, spa, ssr, mdx, yew hooks, bazel",
-            published: true,
-        },
-        &post2::post_2,
-    ),
-    (
-        Metadata {
-            date: date!(2022 - 2 - 15),
-            slug: "building-a-blog-like-its-2022",
-            title: "Building a Blog Like it's 2022 ✨",
-            subtitle: "With Next.js, typescript, react, mdx, rust + wasm",
-            published: true,
-        },
-        &post1::post,
-    ),
-];
+    pub title: Cow<'static, str>,
+    pub date: time::Date,
+    //  last significant edit, shown next to `date`
+    pub updated: Option<time::Date>,
+    pub author: Cow<'static, str>,
+    pub slug: Cow<'static, str>,
+    pub subtitle: Cow<'static, str>,
+    pub published: bool,
+    //  publishes the post once the clock passes it, overriding `published`
+    pub publish_at: Option<time::OffsetDateTime>,
+    pub tags: Cow<'static, [Cow<'static, str>]>,
+    pub series: Option<SeriesPart>,
+    //  show a table of contents of `headings`
+    pub toc: bool,
+    //  counted by build.rs, see `frontmatter::word_count`
+    pub words: u32,
+    //  `#` to `###` headings in document order, see `extract::headings`
+    pub headings: Cow<'static, [Heading]>,
+    //  info strings of the fenced code blocks, see `extract::fences`
+    pub fences: Cow<'static, [Cow<'static, str>]>,
+    //  plain text of the body for search, see `extract::plain_text`
+    #[serde(skip)]
+    pub text: Cow<'static, str>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesPart {
+    //  title of the series
+    pub title: Cow<'static, str>,
+    //  1-based position of the post within its series
+    pub part: u32,
+}
+
+impl Metadata {
+    //  `now` is the server's clock, see `PageContext`, so scheduled posts go
+    //  live without a rebuild
+    pub fn is_published(&self, now: time::OffsetDateTime) -> bool {
+        match self.publish_at {
+            Some(at) => now >= at,
+            None => self.published,
+        }
+    }
+
+    //  drafts are only listed when built with `--//:show_drafts`
+    pub fn is_visible(&self, now: time::OffsetDateTime) -> bool {
+        SHOW_DRAFTS || self.is_published(now)
+    }
+
+    //  at a typical 200 words per minute, rounded up
+    pub fn reading_minutes(&self) -> u32 {
+        (f64::from(self.words) / 200.0).ceil().max(1.0) as u32
+    }
+
+    //  `Oct 01, 2022 · 1234 words · 7 min read`
+    fn byline(&self) -> String {
+        format!(
+            "{} · {} words · {} min read",
+            format_date(self.date),
+            self.words,
+            self.reading_minutes()
+        )
+    }
+}
+
+fn format_date(date: time::Date) -> String {
+    let fmt = time::macros::format_description!("[month repr:short] [day], [year]");
+    date.format(&fmt).unwrap_or_default()
+}
+
+//  `BLOG_POSTS`, newest first, and a render function per post, generated by
+//  build.rs from the frontmatter and body of each `src/blog/posts/*.mdx`
+include!(concat!(env!("OUT_DIR"), "/posts.rs"));
+
+lazy_static! {
+    //  `BLOG_POSTS`' metadata, shared like runtime content's
+    static ref COMPILED: Vec<Arc<Metadata>> = BLOG_POSTS
+        .iter()
+        .map(|(md, _)| Arc::new(md.clone()))
+        .collect();
+}
 
-pub fn render(slug: &str) -> Html {
-    let post_content = BLOG_POSTS
+//  every post, newest first, with runtime content replacing compiled posts of
+//  the same slug
+fn all_posts() -> Vec<Arc<Metadata>> {
+    let runtime = content::posts();
+    let mut posts: Vec<_> = COMPILED
         .iter()
-        .find(|(meta, _)| &slug == &meta.slug)
-        .map(|(meta, post)| post(meta))
-        .unwrap_or(mdx! {r#"Post not found :("#});
+        .filter(|md| !runtime.iter().any(|r| r.slug == md.slug))
+        .cloned()
+        .collect();
+    posts.extend(runtime);
+    posts.sort_by_key(|md| std::cmp::Reverse(md.date));
+    posts
+}
+
+//  posts that should appear in the index and feeds, newest first
+pub fn visible_posts(now: time::OffsetDateTime) -> impl Iterator<Item = Arc<Metadata>> {
+    all_posts().into_iter().filter(move |md| md.is_visible(now))
+}
+
+//  every tag used by a visible post, sorted and deduplicated
+pub fn tags(now: time::OffsetDateTime) -> Vec<String> {
+    let mut tags: Vec<_> = visible_posts(now)
+        .flat_map(|md| md.tags.to_vec())
+        .map(Cow::into_owned)
+        .collect();
+    tags.sort_unstable();
+    tags.dedup();
+    tags
+}
+
+pub fn slugs(now: time::OffsetDateTime) -> impl Iterator<Item = String> {
+    visible_posts(now).map(|md| md.slug.to_string())
+}
+
+//  built with `--//:show_drafts`, which lists and serves drafts like
+//  published posts
+pub const SHOW_DRAFTS: bool = option_env!("SHOW_UNPUBLISHED").is_some();
+
+//  query parameter carrying a signed preview token, see `server/src/preview.rs`
+pub const PREVIEW_PARAM: &str = "preview";
+
+//  the post at `slug` if it can be viewed, drafts and scheduled posts only
+//  with `preview`
+pub fn find_post(slug: &str, now: time::OffsetDateTime, preview: bool) -> Option<Arc<Metadata>> {
+    all_posts()
+        .into_iter()
+        .find(|md| md.slug == slug && (preview || md.is_visible(now)))
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PostProps {
+    pub slug: String,
+}
+
+//  `/blog/:slug`. Only the server can check a preview token, drafts are shown
+//  for the post it verified one for, see `PageContext::preview`.
+#[function_component]
+pub fn Post(props: &PostProps) -> Html {
+    let page = use_context::<PageContext>().unwrap();
+    let (now, preview) = (page.now, page.preview.as_ref() == Some(&props.slug));
+    //  following a `Link` to another post keeps the scroll position, so start
+    //  the new post at its top. Not on first render, which may be for a
+    //  `#heading` link.
+    let shown = use_mut_ref(|| props.slug.clone());
+    use_effect_with_deps(
+        move |slug: &String| {
+            //  the server only sends a runtime post's body with its own page,
+            //  e.g. going back to it from another page needs a reload
+            if content::is_runtime(slug) && content::body(slug).is_none() {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.location().reload();
+                }
+            } else if *shown.borrow() != *slug {
+                if let Some(window) = web_sys::window() {
+                    window.scroll_to_with_x_and_y(0.0, 0.0);
+                }
+                *shown.borrow_mut() = slug.clone();
+            }
+            || ()
+        },
+        props.slug.clone(),
+    );
+    render(&props.slug, now, preview)
+}
+
+pub fn render(slug: &str, now: time::OffsetDateTime, preview: bool) -> Html {
+    let Some(meta) = find_post(slug, now, preview) else {
+        return html! { <crate::NotFound /> };
+    };
+    let post_content = if content::is_runtime(&meta.slug) {
+        markdown::render(&content::body(&meta.slug).unwrap_or_default())
+    } else {
+        match BLOG_POSTS.iter().find(|(md, _)| md.slug == slug) {
+            Some((_, post)) => post(&meta),
+            None => html! {},
+        }
+    };
+    let headings = if meta.toc {
+        meta.headings.to_vec()
+    } else {
+        vec![]
+    };
+    let (toc_inline, toc_sidebar) = if headings.is_empty() {
+        (html! {}, html! {})
+    } else {
+        (
+            html! {
+              <details class="xl:hidden my-4 p-4 rounded bg-gray-300/20">
+                <summary class="cursor-pointer">{"Contents"}</summary>
+                <TableOfContents headings={headings.clone()} />
+              </details>
+            },
+            html! {
+              <aside class="hidden xl:block sticky top-4 self-start w-72 max-h-screen overflow-auto py-10">
+                <TableOfContents headings={headings} />
+              </aside>
+            },
+        )
+    };
+    //  keyed by slug so headings take their slugs afresh when navigating
+    //  between posts
     html! {
-      <div class="w-full md:max-w-4xl p-2">
-        {post_content}
+      <div key={&*meta.slug} class="w-full flex justify-center gap-8">
+        <div class="w-full md:max-w-4xl p-2">
+          {post_header(&meta)}
+          {series_contents(&meta, now)}
+          {toc_inline}
+          <ContextProvider<BodyContext> context={BodyContext::new(&meta)}>
+            {post_content}
+          </ContextProvider<BodyContext>>
+          {series_links(&meta, now)}
+          {post_footer(&meta, now)}
+        </div>
+        {toc_sidebar}
       </div>
     }
 }
 
-pub fn blog_index() -> Html {
-    let fmt = time::macros::format_description!("[month repr:short] [day], [year]");
-    BLOG_POSTS
-        .iter()
-        .filter(|(md, _)| option_env!("SHOW_UNPUBLISHED").is_some() || md.published)
-        .map(|(metadata, _)| {
+//  Title, author, dates and tags of every post come from its frontmatter
+//  rather than the MDX body, so they look the same across posts.
+fn post_header(meta: &Metadata) -> Html {
+    let subtitle = (!meta.subtitle.is_empty()).then(|| {
+        html! { <div class="text-2xl pt-2">{&meta.subtitle}</div> }
+    });
+    let updated = meta.updated.map(|updated| {
+        html! { <div class="text-base">{format!("Updated {}", format_date(updated))}</div> }
+    });
+    html! {
+      <header class="pt-10 pb-4">
+        <h1 class="text-5xl font-display">{&meta.title}</h1>
+        {for subtitle}
+        <div class="text-xl pt-4">{format!("{} · {}", meta.author, meta.byline())}</div>
+        {for updated}
+        {tag_list(&meta.tags)}
+      </header>
+    }
+}
+
+//  The published posts just before and after `meta` in time. Posts from the
+//  same day are ordered by slug, and drafts get neighbours too, so the footer
+//  looks the same in a preview as once published.
+fn neighbours(
+    meta: &Metadata,
+    now: time::OffsetDateTime,
+) -> (Option<Arc<Metadata>>, Option<Arc<Metadata>>) {
+    let key = |md: &Metadata| (md.date, md.slug.clone());
+    let published = || {
+        all_posts()
+            .into_iter()
+            .filter(|md| md.is_published(now) && md.slug != meta.slug)
+    };
+    let prev = published()
+        .filter(|md| key(md) < key(meta))
+        .max_by_key(|md| key(md));
+    let next = published()
+        .filter(|md| key(md) > key(meta))
+        .min_by_key(|md| key(md));
+    (prev, next)
+}
+
+//  A link to the post at `md`. Runtime posts' bodies come with a full page
+//  load, so those skip the router.
+fn post_link(md: &Metadata, classes: &'static str, children: Html) -> Html {
+    if content::is_runtime(&md.slug) {
+        let href = Route::BlogPost {
+            slug: md.slug.to_string(),
+        }
+        .to_path();
+        return html! { <a class={classes} href={href}>{children}</a> };
+    }
+    html! {
+      <Link<Route> classes={classes} to={Route::BlogPost { slug: md.slug.to_string() }}>
+        {children}
+      </Link<Route>>
+    }
+}
+
+fn post_footer(meta: &Metadata, now: time::OffsetDateTime) -> Html {
+    let link = |md: &Metadata, label: &str| {
+        post_link(
+            md,
+            "text-inherit",
+            html! {
+              <>
+                <div class="text-sm">{label.to_owned()}</div>
+                <div class="text-xl">{&md.title}</div>
+              </>
+            },
+        )
+    };
+    let (prev, next) = neighbours(meta, now);
+    html! {
+      <footer class="flex justify-between flex-wrap gap-4 py-6 border-t border-gray-300/40">
+        <div>{for prev.map(|md| link(&md, "← Previous"))}</div>
+        <div class="text-right">{for next.map(|md| link(&md, "Next →"))}</div>
+      </footer>
+    }
+}
+
+//  visible parts of `meta`'s series in order, always including `meta` itself
+fn series_posts(meta: &Metadata, now: time::OffsetDateTime) -> Vec<Arc<Metadata>> {
+    let Some(current) = &meta.series else {
+        return vec![];
+    };
+    let mut posts: Vec<_> = all_posts()
+        .into_iter()
+        .filter(|md| md.is_visible(now) || md.slug == meta.slug)
+        .filter(|md| matches!(&md.series, Some(s) if s.title == current.title))
+        .collect();
+    posts.sort_by_key(|md| md.series.as_ref().map(|s| s.part));
+    posts
+}
+
+fn series_contents(meta: &Metadata, now: time::OffsetDateTime) -> Html {
+    let Some(current) = &meta.series else {
+        return html! {};
+    };
+    let parts = series_posts(meta, now).into_iter().map(|md| {
+        let part = md.series.as_ref().map(|s| s.part).unwrap_or_default();
+        let label = format!("Part {part}: {}", md.title);
+        if md.slug == meta.slug {
+            html! { <li class="py-1 font-bold">{label}</li> }
+        } else {
+            html! {
+              <li class="py-1">{post_link(&md, "", html! { {label} })}</li>
+            }
+        }
+    });
+    html! {
+      <div class="my-4 p-4 rounded bg-gray-300/20">
+        <div class="text-xl pb-2">
+          {format!("This post is part {} of the {} series", current.part, current.title)}
+        </div>
+        <ol>{for parts}</ol>
+      </div>
+    }
+}
+
+fn series_links(meta: &Metadata, now: time::OffsetDateTime) -> Html {
+    let posts = series_posts(meta, now);
+    let Some(idx) = posts.iter().position(|md| md.slug == meta.slug) else {
+        return html! {};
+    };
+    let link = |md: &Metadata, label: &str| {
+        post_link(md, "text-xl", html! { {format!("{label} {}", md.title)} })
+    };
+    let prev = idx.checked_sub(1).and_then(|i| posts.get(i));
+    let next = posts.get(idx + 1);
+    html! {
+      <div class="flex justify-between flex-wrap gap-4 py-6">
+        <div>{for prev.map(|md| link(md, "←"))}</div>
+        <div>{for next.map(|md| link(md, "→"))}</div>
+      </div>
+    }
+}
+
+fn tag_list(tags: &[Cow<'static, str>]) -> Html {
+    html! {
+      <div class="flex flex-wrap gap-2 py-2">
+        {
+          for tags.iter().map(|tag| html! {
+            <Link<Route> classes="text-sm px-2 rounded bg-gray-300/40 dark:bg-gray-300/20" to={Route::BlogTag { tag: tag.to_string() }}>
+              {format!("#{tag}")}
+            </Link<Route>>
+          })
+        }
+      </div>
+    }
+}
+
+fn post_list(posts: impl Iterator<Item = Arc<Metadata>>) -> Html {
+    posts
+        .map(|metadata| {
+            let summary = html! {
+              <>
+                <h1 class="text-4xl font-display">
+                  {&metadata.title}
+                </h1>
+                <div> {&metadata.subtitle} </div>
+                <div class="text-xl"> {metadata.byline()} </div>
+              </>
+            };
             html! {
               <div class="py-4">
-                <Link<Route> classes="text-inherit" to={Route::BlogPost { slug: metadata.slug.into() }}>
-                  <h1 class="text-4xl font-display">
-                    {&metadata.title}
-                  </h1>
-                  <div> {&metadata.subtitle} </div>
-                  <div class="text-xl"> {&metadata.date.clone().format(&fmt).unwrap_or_default()} </div>
-                </Link<Route>>
+                {post_link(&metadata, "text-inherit", summary)}
+                {tag_list(&metadata.tags)}
               </div>
             }
         })
         .collect()
 }
+
+#[function_component]
+pub fn BlogIndex() -> Html {
+    let now = use_context::<PageContext>().unwrap().now;
+    post_list(visible_posts(now))
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TagIndexProps {
+    pub tag: String,
+}
+
+#[function_component]
+pub fn TagIndex(props: &TagIndexProps) -> Html {
+    let now = use_context::<PageContext>().unwrap().now;
+    let tag = &props.tag;
+    html! {
+      <>
+        <h1 class="text-4xl font-display py-4">{format!("#{tag}")}</h1>
+        {post_list(visible_posts(now).filter(|md| md.tags.iter().any(|t| t == tag)))}
+      </>
+    }
+}
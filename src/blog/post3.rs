@@ -1,5 +0,0 @@
-use super::*;
-
-pub fn post_3(_: &Metadata) -> Html {
-    include_mdx!("src/blog/post3.mdx")
-}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use lazy_static::lazy_static;
+use yew::prelude::*;
+use yew_router::prelude::*;
+
+use super::{all_posts, content, post_link, Metadata};
+use crate::{PageContext, Route};
+
+//  bytes of context on either side of the first match in a snippet
+const SNIPPET_CONTEXT: usize = 80;
+
+//  A post's searchable text, extracted by build.rs for compiled posts and
+//  when loading runtime content, see `Metadata::text`
+struct Doc {
+    meta: Arc<Metadata>,
+    //  `fold`ed so byte offsets line up with the text
+    lower: String,
+}
+
+lazy_static! {
+    //  built for the runtime content generation in `.0`
+    static ref INDEX: Mutex<(Option<u64>, Arc<Vec<Doc>>)> = Default::default();
+}
+
+fn index() -> Arc<Vec<Doc>> {
+    let mut index = INDEX.lock().unwrap();
+    let generation = content::generation();
+    if index.0 != Some(generation) {
+        let docs = all_posts()
+            .into_iter()
+            .map(|meta| Doc {
+                lower: fold(&meta.text),
+                meta,
+            })
+            .collect();
+        *index = (Some(generation), Arc::new(docs));
+    }
+    index.1.clone()
+}
+
+//  `s` lowercased char by char. Chars whose lowercase is longer or shorter,
+//  like `İ`, are kept as they are so byte offsets into `s` stay valid.
+fn fold(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            let mut lower = c.to_lowercase();
+            match (lower.next(), lower.next()) {
+                (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+                _ => c,
+            }
+        })
+        .collect()
+}
+
+pub struct SearchResult {
+    pub meta: Arc<Metadata>,
+    pub score: usize,
+    //  body text around the first match, with the byte ranges of every match
+    pub snippet: String,
+    pub matches: Vec<std::ops::Range<usize>>,
+}
+
+fn terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<_> = query.split_whitespace().map(fold).collect();
+    terms.sort_unstable();
+    terms.dedup();
+    terms
+}
+
+//  Posts containing every term of `query`, best matches first. Matches in the
+//  title count for more than tags, the subtitle and then the body.
+pub fn search(query: &str, now: time::OffsetDateTime) -> Vec<SearchResult> {
+    let terms = terms(query);
+    if terms.is_empty() {
+        return vec![];
+    }
+    let mut results: Vec<_> = index()
+        .iter()
+        //  checked per search, scheduled posts are indexed before they go live
+        .filter(|doc| doc.meta.is_visible(now))
+        .filter_map(|doc| {
+            let title = fold(&doc.meta.title);
+            let subtitle = fold(&doc.meta.subtitle);
+            let mut score = 0;
+            for term in &terms {
+                let term_score = 10 * title.matches(term.as_str()).count()
+                    + 5 * doc
+                        .meta
+                        .tags
+                        .iter()
+                        .filter(|t| fold(t).contains(term.as_str()))
+                        .count()
+                    + 3 * subtitle.matches(term.as_str()).count()
+                    + doc.lower.matches(term.as_str()).count().min(20);
+                if term_score == 0 {
+                    return None;
+                }
+                score += term_score;
+            }
+            let (snippet, matches) = snippet(doc, &terms);
+            Some(SearchResult {
+                meta: doc.meta.clone(),
+                score,
+                snippet,
+                matches,
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| b.score.cmp(&a.score).then(b.meta.date.cmp(&a.meta.date)));
+    results
+}
+
+fn snippet(doc: &Doc, terms: &[String]) -> (String, Vec<std::ops::Range<usize>>) {
+    let text = &doc.meta.text;
+    let first = terms
+        .iter()
+        .filter_map(|t| doc.lower.find(t.as_str()))
+        .min()
+        .unwrap_or(0);
+    let mut start = first.saturating_sub(SNIPPET_CONTEXT);
+    let mut end = (first + SNIPPET_CONTEXT).min(text.len());
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    //  don't start or end in the middle of a word
+    if start > 0 {
+        start = text[start..first]
+            .find(' ')
+            .map_or(start, |i| start + i + 1);
+    }
+    if end < text.len() {
+        end = text[first..end].rfind(' ').map_or(end, |i| first + i);
+    }
+
+    let mut snippet = text[start..end].to_owned();
+    let lower = &doc.lower[start..end];
+    let mut matches: Vec<_> = terms
+        .iter()
+        .flat_map(|t| lower.match_indices(t.as_str()).map(|(i, m)| i..i + m.len()))
+        .collect();
+    matches.sort_by_key(|m| m.start);
+    //  overlapping matches, e.g. `rust` inside `rustc` when searching both
+    matches.dedup_by(|b, a| {
+        if b.start < a.end {
+            a.end = a.end.max(b.end);
+            true
+        } else {
+            false
+        }
+    });
+    let offset = if start > 0 {
+        snippet.insert_str(0, "… ");
+        "… ".len()
+    } else {
+        0
+    };
+    if end < text.len() {
+        snippet += " …";
+    }
+    let matches = matches
+        .into_iter()
+        .map(|m| m.start + offset..m.end + offset)
+        .collect();
+    (snippet, matches)
+}
+
+fn highlighted(text: &str, matches: &[std::ops::Range<usize>]) -> Html {
+    let mut out = vec![];
+    let mut last = 0;
+    for m in matches {
+        out.push(html! { {&text[last..m.start]} });
+        out.push(html! { <mark>{&text[m.clone()]}</mark> });
+        last = m.end;
+    }
+    out.push(html! { {&text[last..]} });
+    out.into_iter().collect()
+}
+
+//  `/search?q=...`. The server renders the query it was requested with, see
+//  `PageContext::query`, the client then reads it from its location. Typing
+//  updates the query in place; without javascript the form submits instead.
+#[function_component]
+pub fn Search() -> Html {
+    let page = use_context::<PageContext>().unwrap();
+    let location = use_location();
+    let navigator = use_navigator();
+    let query = match page.query {
+        Some(query) => query,
+        None => location
+            .and_then(|l| l.query::<HashMap<String, String>>().ok())
+            .and_then(|mut q| q.remove("q"))
+            .unwrap_or_default(),
+    };
+    //  the server only sends the text of runtime posts with this page, e.g.
+    //  following a link to it needs a reload
+    use_effect_with_deps(
+        |_| {
+            if !content::has_texts() {
+                if let Some(window) = web_sys::window() {
+                    let _ = window.location().reload();
+                }
+            }
+            || ()
+        },
+        (),
+    );
+
+    let oninput = Callback::from(move |e: InputEvent| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        if let Some(navigator) = &navigator {
+            let _ = navigator.replace_with_query(&Route::Search, &[("q", input.value())]);
+        }
+    });
+
+    let results = search(&query, page.now);
+    let summary = match (query.trim().is_empty(), results.len()) {
+        (true, _) => String::new(),
+        (false, 1) => "1 post found".into(),
+        (false, n) => format!("{n} posts found"),
+    };
+    let results = results.iter().map(|r| {
+        html! {
+          <div class="py-4">
+            {post_link(&r.meta, "text-inherit", html! {
+              <>
+                <h1 class="text-3xl font-display">{&r.meta.title}</h1>
+                <div>{&r.meta.subtitle}</div>
+              </>
+            })}
+            <p class="py-2 opacity-80">{highlighted(&r.snippet, &r.matches)}</p>
+          </div>
+        }
+    });
+
+    html! {
+      <div class="w-full md:max-w-3xl p-2">
+        <form action="/search" method="get" class="py-4">
+          <input
+            type="search"
+            name="q"
+            value={query}
+            oninput={oninput}
+            placeholder="Search posts"
+            class="w-full p-2 text-xl rounded bg-gray-300/20"
+          />
+        </form>
+        <div class="text-xl">{summary}</div>
+        {for results}
+      </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fold_lowercases_unicode() {
+        assert_eq!(fold("Ärger ÜBER Straße"), "ärger über straße");
+    }
+
+    #[test]
+    fn fold_keeps_byte_offsets() {
+        //  `İ` lowercases to two chars, the kelvin sign to a one byte `k`
+        let s = "İstanbul \u{212A}elvin";
+        assert_eq!(fold(s), s);
+    }
+
+    #[test]
+    fn terms_are_folded_and_deduplicated() {
+        assert_eq!(terms("Rust  rust ÜBER"), ["rust", "über"]);
+    }
+}
@@ -0,0 +1,99 @@
+//  Heading anchors. Shared with build.rs, which slugs the headings of the
+//  compiled posts, so only std here.
+
+use std::collections::HashSet;
+
+//  max length of an anchor slug, in chars
+const HEADER_LINK_LEN: usize = 20;
+
+//  Lowercases `text`, joins words with `-` and drops punctuation and emoji.
+//  Non-ascii letters and digits are kept.
+pub fn slugify(text: &str) -> String {
+    let mut out = String::new();
+    let mut len = 0;
+    for c in text.chars() {
+        if len == HEADER_LINK_LEN {
+            break;
+        }
+        if c.is_alphanumeric() {
+            out.extend(c.to_lowercase());
+            len += 1;
+        } else if (c.is_whitespace() || c == '-' || c == '_')
+            && !out.is_empty()
+            && !out.ends_with('-')
+        {
+            out.push('-');
+            len += 1;
+        }
+    }
+    out.trim_end_matches('-').to_owned()
+}
+
+//  Hands out unique slugs within a single post, suffixing repeats with `-1`,
+//  `-2`, ... in document order.
+#[derive(Default)]
+pub struct Slugger {
+    seen: HashSet<String>,
+}
+
+impl Slugger {
+    pub fn slug(&mut self, text: &str) -> String {
+        let mut base = slugify(text);
+        if base.is_empty() {
+            base = "section".into();
+        }
+        let mut slug = base.clone();
+        let mut n = 0;
+        while self.seen.contains(&slug) {
+            n += 1;
+            slug = format!("{base}-{n}");
+        }
+        self.seen.insert(slug.clone());
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_are_numbered() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("Intro"), "intro");
+        assert_eq!(slugger.slug("Intro"), "intro-1");
+        assert_eq!(slugger.slug("intro"), "intro-2");
+        assert_eq!(slugger.slug("Intro 1"), "intro-1-1");
+    }
+
+    #[test]
+    fn unicode_is_kept() {
+        assert_eq!(slugify("Café crème"), "café-crème");
+        assert_eq!(slugify("Ünïcode ÄÖ"), "ünïcode-äö");
+        assert_eq!(slugify("日本語 テキスト"), "日本語-テキスト");
+    }
+
+    #[test]
+    fn punctuation_is_dropped() {
+        assert_eq!(slugify("Next.js + React = 💖"), "nextjs-react");
+        assert_eq!(slugify("What's `Pin<T>`?"), "whats-pint");
+        assert_eq!(
+            slugify("  snake_case -- and kebab  "),
+            "snake-case-and-kebab"
+        );
+    }
+
+    #[test]
+    fn long_headings_are_truncated() {
+        let slug = slugify("A heading that goes on for quite a while");
+        assert_eq!(slug, "a-heading-that-goes");
+        assert!(slug.chars().count() <= HEADER_LINK_LEN);
+    }
+
+    #[test]
+    fn empty_slugs_get_a_name() {
+        let mut slugger = Slugger::default();
+        assert_eq!(slugger.slug("💖"), "section");
+        assert_eq!(slugger.slug("!?"), "section-1");
+    }
+}
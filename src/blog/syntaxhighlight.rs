@@ -1,48 +1,173 @@
-use web_sys::{console, Element};
+use std::ops::RangeInclusive;
+
 use yew::{prelude::*, virtual_dom::VNode};
 
+use wasm_bindgen_futures::JsFuture;
+
+use super::{highlight::highlight_lines, html_text, use_fence_info};
+
+//  Options from a fence info string, e.g.
+//  ```rust title="src/main.rs" showLineNumbers {3-5,8}
+#[derive(Default, Debug, PartialEq)]
+struct FenceInfo {
+    lang: String,
+    title: Option<String>,
+    line_numbers: bool,
+    highlighted: Vec<RangeInclusive<usize>>,
+}
+
+impl FenceInfo {
+    fn parse(info: &str) -> Self {
+        let mut out = FenceInfo::default();
+        let mut rest = info.trim();
+        while !rest.is_empty() {
+            let (word, after) = if let Some(title) = rest.strip_prefix("title=\"") {
+                let end = title.find('"').unwrap_or(title.len());
+                out.title = Some(title[..end].to_owned());
+                ("", title.get(end + 1..).unwrap_or_default())
+            } else {
+                rest.split_once(char::is_whitespace).unwrap_or((rest, ""))
+            };
+            if let Some(ranges) = word.strip_prefix('{').and_then(|w| w.strip_suffix('}')) {
+                out.highlighted
+                    .extend(ranges.split(',').filter_map(parse_range));
+            } else if matches!(word, "showLineNumbers" | "lineNumbers" | "linenos") {
+                out.line_numbers = true;
+            } else if out.lang.is_empty() && !word.is_empty() {
+                out.lang = word.to_owned();
+            }
+            rest = after.trim_start();
+        }
+        out
+    }
+
+    fn is_highlighted(&self, line: usize) -> bool {
+        self.highlighted.iter().any(|r| r.contains(&line))
+    }
+}
+
+//  `3` or `3-5`, 1-based
+fn parse_range(s: &str) -> Option<RangeInclusive<usize>> {
+    let (start, end) = s.split_once('-').unwrap_or((s, s));
+    Some(start.trim().parse().ok()?..=end.trim().parse().ok()?)
+}
+
+#[derive(Properties, PartialEq)]
+struct CopyButtonProps {
+    code: AttrValue,
+}
+
+//  only rendered once hydrated, since it needs the clipboard api
+#[function_component]
+fn CopyButton(props: &CopyButtonProps) -> Html {
+    let hydrated = use_state(|| false);
+    let copied = use_state(|| false);
+    {
+        let hydrated = hydrated.clone();
+        use_effect_with_deps(
+            move |_| {
+                hydrated.set(true);
+                || ()
+            },
+            (),
+        );
+    }
+    if !*hydrated {
+        return html! {};
+    }
+    let onclick = {
+        let code = props.code.clone();
+        let copied = copied.clone();
+        move |_| {
+            let Some(clipboard) = web_sys::window().and_then(|w| w.navigator().clipboard()) else {
+                log::warn!("no clipboard api");
+                return;
+            };
+            //  rejected without permission or outside a secure context
+            let write = JsFuture::from(clipboard.write_text(&code));
+            let copied = copied.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match write.await {
+                    Ok(_) => copied.set(true),
+                    Err(e) => log::warn!("copying code failed: {e:?}"),
+                }
+            });
+        }
+    };
+    html! {
+        <button
+            class="absolute top-2 right-6 px-2 text-sm rounded bg-gray-300/30 opacity-0 group-hover:opacity-100 focus:opacity-100"
+            onclick={onclick}
+        >
+            { if *copied { "Copied!" } else { "Copy" } }
+        </button>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CodeProps {
+    #[prop_or_default]
+    pub children: Children,
+    //  fence info string of a block rendered from markdown, compiled posts
+    //  take theirs from `BodyContext`
+    #[prop_or_default]
+    pub info: Option<AttrValue>,
+}
+
+//  Highlights the `<code>` block inside a fenced `pre` at render time, so SSR
+//  output is already highlighted and nothing changes on hydration.
 #[function_component]
-pub fn HighlightCode(c: &super::ChildProps) -> Html {
-    //  IDEA: try NodeRef and call Prism.highlightElement on it
-    //      but how do we store a ref??
-    let code_ref = use_state_eq(|| NodeRef::default());
-    let mut code_tag = c.children.iter().next().unwrap().clone();
-    match &mut code_tag {
-        VNode::VTag(t) => t.node_ref = (*code_ref).clone(),
-        _ => {}
+pub fn HighlightCode(c: &CodeProps) -> Html {
+    let info = FenceInfo::parse(&use_fence_info(c.info.clone()));
+    let Some(VNode::VTag(code_tag)) = c.children.iter().next() else {
+        return html! {
+            <pre class="overflow-auto m-4 p-6 bg-gray-300/5 rounded">
+                {c.children.clone()}
+            </pre>
+        };
     };
+    let mut code = String::new();
+    html_text(&VNode::VTag(code_tag), &mut code);
+    let lang_class = format!("language-{}", info.lang);
 
-    use_effect_with_deps(
-        move |_| {
-            console::log_1(&"highlighting...".to_string().into());
-            let element = code_ref.cast::<Element>().unwrap();
-            prism::highlightElement(element.clone());
-            move || {
-                element
-                    .closest(".codecontainer")
-                    .ok()
-                    .flatten()
-                    .map(|e| e.remove());
+    let lines = highlight_lines(&info.lang, &code)
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let line_no = i + 1;
+            let class = info
+                .is_highlighted(line_no)
+                .then_some("inline-block min-w-full bg-gray-300/20");
+            let line_number = if info.line_numbers {
+                html! { <span class="inline-block w-8 pr-4 text-right select-none opacity-50">{line_no}</span> }
+            } else {
+                html! {}
+            };
+            html! {
+                <>
+                <span class={classes!(class)}>
+                    {line_number}
+                    {line}
+                </span>
+                {"\n"}
+                </>
             }
+        });
+
+    let title = match &info.title {
+        Some(title) => html! {
+            <div class="mx-4 -mb-4 px-6 py-1 text-sm font-mono rounded-t bg-gray-300/20">{title}</div>
         },
-        c.children.clone(),
-    );
+        None => html! {},
+    };
 
     html! {
-        <div class="codecontainer">
-            <pre class="overflow-auto m-4 p-6 bg-gray-300/5 rounded">
-                {code_tag}
+        <div class="codecontainer relative group">
+            {title}
+            <CopyButton code={code.clone()} />
+            <pre class={classes!("overflow-auto", "m-4", "p-6", "bg-gray-300/5", "rounded", lang_class.clone())}>
+                <code class={lang_class}>{for lines}</code>
             </pre>
         </div>
     }
 }
-
-mod prism {
-    use wasm_bindgen::prelude::*;
-
-    #[wasm_bindgen]
-    extern "C" {
-        #[wasm_bindgen(js_namespace = Prism)]
-        pub fn highlightElement(element: web_sys::Element);
-    }
-}
@@ -0,0 +1,62 @@
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use yew::prelude::*;
+
+//  A `#` to `###` heading of a post, extracted from its MDX by
+//  `extract::headings` with the slug its heading component uses as `id`
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+pub struct Heading {
+    pub level: usize,
+    pub text: Cow<'static, str>,
+    pub slug: Cow<'static, str>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TocProps {
+    pub headings: Vec<Heading>,
+}
+
+#[function_component]
+pub fn TableOfContents(props: &TocProps) -> Html {
+    if props.headings.is_empty() {
+        return html! {};
+    }
+    html! {
+      <nav class="text-base">
+        <div class="font-bold pb-2">{"Contents"}</div>
+        {outline(&props.headings)}
+      </nav>
+    }
+}
+
+//  nest each heading's subheadings (anything deeper until the next heading at
+//  the same level or above) in a child list
+fn outline(headings: &[Heading]) -> Html {
+    let mut items = vec![];
+    let mut i = 0;
+    while i < headings.len() {
+        let heading = &headings[i];
+        let end = headings[i + 1..]
+            .iter()
+            .position(|h| h.level <= heading.level)
+            .map_or(headings.len(), |p| i + 1 + p);
+        let children = &headings[i + 1..end];
+        items.push(html! {
+          <li class="py-1">
+            <a class="text-inherit" href={format!("#{}", heading.slug)}>{&heading.text}</a>
+            {
+              if children.is_empty() {
+                  html! {}
+              } else {
+                  outline(children)
+              }
+            }
+          </li>
+        });
+        i = end;
+    }
+    html! {
+      <ul class="pl-4">{for items}</ul>
+    }
+}
@@ -1,4 +1,4 @@
-mod blog;
+pub mod blog;
 mod projects;
 
 use std::collections::HashMap;
@@ -18,16 +18,82 @@ pub enum Route {
     BlogIndex,
     #[at("/blog/:slug")]
     BlogPost { slug: String },
+    #[at("/blog/tag/:tag")]
+    BlogTag { tag: String },
     #[at("/projects")]
     Projects,
+    #[at("/search")]
+    Search,
+    #[not_found]
+    #[at("/404")]
+    NotFound,
+}
+
+impl Route {
+    //  every page on the site, used when exporting a static mirror. Not
+    //  `/search`, which is only useful with a query.
+    pub fn all(now: time::OffsetDateTime) -> Vec<Route> {
+        let mut routes = vec![Route::Home, Route::BlogIndex, Route::Projects];
+        routes.extend(blog::slugs(now).map(|slug| Route::BlogPost { slug }));
+        routes.extend(
+            blog::tags(now)
+                .into_iter()
+                .map(|tag| Route::BlogTag { tag }),
+        );
+        routes
+    }
+
+    //  whether the server should answer this route with a 404 status, drafts
+    //  found through a valid preview link are handled by the server
+    pub fn is_not_found(&self, now: time::OffsetDateTime) -> bool {
+        match self {
+            Route::BlogPost { slug } => blog::find_post(slug, now, false).is_none(),
+            Route::BlogTag { tag } => !blog::tags(now).contains(tag),
+            Route::NotFound => true,
+            _ => false,
+        }
+    }
+}
+
+//  What every page is rendered with, decided by the server and embedded in
+//  the page so the client hydrates the same tree
+#[derive(Clone, PartialEq, Debug)]
+pub struct PageContext {
+    //  the server's clock when rendering, scheduled posts are shown once it
+    //  passes their `publish_at`
+    pub now: time::OffsetDateTime,
+    //  slug of the post the page was requested with a valid preview link
+    //  for, other posts reached from it are not previews
+    pub preview: Option<String>,
+    //  `q` of the request when rendering on the server, the client reads its
+    //  location instead
+    pub query: Option<String>,
+}
+
+//  for pages the server didn't embed a context in
+impl Default for PageContext {
+    fn default() -> Self {
+        PageContext {
+            now: time::OffsetDateTime::now_utc(),
+            preview: None,
+            query: None,
+        }
+    }
+}
+
+#[derive(Properties, PartialEq, Debug)]
+pub struct AppProps {
+    pub page: PageContext,
 }
 
 #[function_component]
-pub fn App() -> Html {
+pub fn App(props: &AppProps) -> Html {
     html! {
-        <BrowserRouter>
-            <Switch<Route> render={switch} />
-        </BrowserRouter>
+        <ContextProvider<PageContext> context={props.page.clone()}>
+            <BrowserRouter>
+                <Switch<Route> render={switch} />
+            </BrowserRouter>
+        </ContextProvider<PageContext>>
     }
 }
 
@@ -35,6 +101,7 @@ pub fn App() -> Html {
 pub struct ServerAppProps {
     pub path: AttrValue,
     pub queries: HashMap<String, String>,
+    pub page: PageContext,
 }
 
 #[function_component]
@@ -43,10 +110,34 @@ pub fn ServerApp(props: &ServerAppProps) -> Html {
     history
         .push_with_query(&*props.path, &props.queries)
         .unwrap();
+    let page = PageContext {
+        query: Some(props.queries.get("q").cloned().unwrap_or_default()),
+        ..props.page.clone()
+    };
+
+    html! {
+        <ContextProvider<PageContext> context={page}>
+            <Router history={history}>
+                <Switch<Route> render={switch} />
+            </Router>
+        </ContextProvider<PageContext>>
+    }
+}
+
+#[derive(Properties, PartialEq, Debug)]
+pub struct ServerPostProps {
+    pub slug: AttrValue,
+    pub now: time::OffsetDateTime,
+}
+
+//  renders a single post without the surrounding page, used for feed entries
+#[function_component]
+pub fn ServerPost(props: &ServerPostProps) -> Html {
+    let history = AnyHistory::from(MemoryHistory::new());
 
     html! {
         <Router history={history}>
-            <Switch<Route> render={switch} />
+            {blog::render(&props.slug, props.now, false)}
         </Router>
     }
 }
@@ -69,6 +160,11 @@ fn Navbar() -> Html {
                         {"Projects"}
                     </button>
                 </Link<Route>>
+                <Link<Route> classes="p-4 text-3xl" to={Route::Search}>
+                    <button >
+                        {"Search"}
+                    </button>
+                </Link<Route>>
                 <a class="p-4 text-3xl" href="https://twitter.com/4kevinking">{"Contact"}</a>
                 <a class="p-4 text-3xl" href="https://github.com/kcking/implfuture.dev">{"GitHub"}</a>
             </div>
@@ -81,6 +177,19 @@ fn Home() -> Html {
     html! {}
 }
 
+#[function_component]
+pub(crate) fn NotFound() -> Html {
+    html! {
+        <div class="w-full flex flex-col items-center my-10 px-2">
+            <h1 class="font-display text-6xl py-6">{"404"}</h1>
+            <div class="text-xl py-2">{"This page could not be found :("}</div>
+            <Link<Route> classes="text-xl py-2" to={Route::BlogIndex}>
+                {"Back to the blog"}
+            </Link<Route>>
+        </div>
+    }
+}
+
 fn switch(route: Route) -> Html {
     html! {
         <main class="font-body flex items-center px-2">
@@ -118,20 +227,52 @@ fn switch(route: Route) -> Html {
                     Route::BlogIndex => html! {
                         <div class="w-full font-body flex px-2 flex-col items-center place-content-around">
                             <div class="flex flex-col">
-                                {blog::blog_index()}
+                                <blog::BlogIndex />
                             </div>
                         </div>
                     },
-                    Route::BlogPost{slug} => {
-                        blog::render(&slug)
-                    }
+                    Route::BlogPost{slug} => html! {
+                        <blog::Post slug={slug} />
+                    },
+                    Route::BlogTag{tag} => html! {
+                        <div class="w-full font-body flex px-2 flex-col items-center place-content-around">
+                            <div class="flex flex-col">
+                                <blog::TagIndex tag={tag} />
+                            </div>
+                        </div>
+                    },
+                    Route::Search => html! {
+                        <div class="w-full font-body flex px-2 flex-col items-center place-content-around">
+                            <blog::Search />
+                        </div>
+                    },
                     Route::Projects => html! {
                         <div class="w-full flex justify-evenly px-2 flex-wrap">
                             <Projects />
                         </div>
                     },
+                    Route::NotFound => html! {
+                        <NotFound />
+                    },
                 }
             }
         </main>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exported_routes_are_published() {
+        let now = time::OffsetDateTime::now_utc();
+        for route in Route::all(now) {
+            if let Route::BlogPost { slug } = &route {
+                let post = blog::find_post(slug, now, true).unwrap();
+                assert!(blog::SHOW_DRAFTS || post.is_published(now), "{slug}");
+            }
+            assert!(!route.is_not_found(now), "{}", route.to_path());
+        }
+    }
+}
@@ -0,0 +1,3 @@
+#!/bin/bash
+# Stamped into the server binary for /version, see server/BUILD
+echo "STABLE_GIT_COMMIT $(git rev-parse HEAD 2>/dev/null || echo unknown)"